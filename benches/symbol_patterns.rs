@@ -0,0 +1,27 @@
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+// NOTE:  cargo bench --bench symbol_patterns
+// Runs a program that matches symbol patterns in a loop with the bench build of mint and reports the fastest run, so
+// that changes to how symbols are represented and matched can be compared.
+fn main() {
+    let program = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/symbol_patterns.mint");
+
+    let mut fastest = Duration::MAX;
+    for _ in 0..5 {
+        let start = Instant::now();
+        let status = Command::new(env!("CARGO_BIN_EXE_mint")).arg(program)
+                                                             .arg("--release")
+                                                             .stdout(Stdio::null())
+                                                             .status()
+                                                             .expect("mint should start");
+        let elapsed = start.elapsed();
+        if !status.success() {
+            eprintln!("symbol_patterns.mint failed");
+            std::process::exit(1);
+        }
+        fastest = fastest.min(elapsed);
+    }
+
+    println!("symbol_patterns: {:?}", fastest);
+}
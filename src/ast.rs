@@ -1,3 +1,6 @@
+use crate::bignum::{BigInt, Rational};
+use crate::runtime::cmp_int_float;
+
 #[derive(Debug, Clone, Copy)]
 pub struct TMeta {
     pub start : usize,
@@ -9,11 +12,17 @@ pub enum Token {
     LowerSymbol(TMeta, String),
     UpperSymbol(TMeta, String),
     Number(TMeta, f64),
+    Int(TMeta, i64),
+    BigInt(TMeta, BigInt),
+    Rational(TMeta, Rational),
     String(TMeta, String),
+    InterpolatedString(TMeta, Vec<StringPart>),
+    Char(TMeta, char),
     LParen(TMeta),
     RParen(TMeta),
     LCurl(TMeta),
     RCurl(TMeta),
+    PercentLCurl(TMeta),
     LSquare(TMeta),
     RSquare(TMeta),
     LAngle(TMeta),
@@ -22,66 +31,232 @@ pub enum Token {
     SRArrow(TMeta),
     DLArrow(TMeta),
     DRArrow(TMeta),
+    DoubleEqual(TMeta),
     Colon(TMeta),
     Dot(TMeta),
+    DotDot(TMeta),
+    DotDotEqual(TMeta),
     Comma(TMeta),
     Equal(TMeta),
     Semicolon(TMeta),
     OrBar(TMeta),
     At(TMeta),
+    PlusPlus(TMeta),
+    Caret(TMeta),
+    Question(TMeta),
+}
+
+// NOTE:  Embedded expressions are tokenized along with the rest of the input and parsed by the parser.  Expr keeps
+// the position of its ${ so that a failure to parse it can be reported there.
+#[derive(Debug)]
+pub enum StringPart {
+    Literal(String),
+    Expr(usize, Vec<Token>),
 }
 
 // TODO see if we can get rid of clone
 
+// NOTE:  Type annotations as written, like List<Number> or fun(a) : a.  Lower case names are type variables.
+#[derive(Debug, Clone)]
+pub enum TypeAnn {
+    Named(String, Vec<TypeAnn>),
+    Var(String),
+    Tuple(Vec<TypeAnn>),
+    Fun(Vec<TypeAnn>, Box<TypeAnn>),
+}
+
+// NOTE:  param_anns has an entry for every parameter.  The name is only known when the lambda is bound directly
+// by a let (let F = fun(X) { X };).  Each ensures clause binds the return value to its variable.  position is the
+// byte offset of fun in the source for stack traces.
 #[derive(Debug, Clone)]
 pub struct Lambda {
+    pub name : Option<String>,
+    pub position : usize,
     pub params : Vec<Pat>,
+    pub param_anns : Vec<Option<TypeAnn>>,
+    pub ret_ann : Option<TypeAnn>,
+    pub requires : Vec<Expr>,
+    pub ensures : Vec<(String, Expr)>,
     pub body : Box<Top>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Lit {
     Number(f64),
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational),
     String(String),
+    Char(char),
     Symbol(String),
     Variable(String),
     List(Vec<Lit>),
     Tuple(Vec<Lit>),
+    Map(Vec<(Lit, Lit)>),
     Lambda(Lambda),
 }
 
+// NOTE:  Int bounds are kept as Ints so that they compare exactly with Int values.  Bounds are compared by value,
+// so 1 and 1.0 are equal bounds.
+#[derive(Debug, Clone, Copy)]
+pub enum Bound {
+    Int(i64),
+    Number(f64),
+}
+
+impl PartialEq for Bound {
+    fn eq(&self, other : &Self) -> bool {
+        self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Bound {
+    fn partial_cmp(&self, other : &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Bound::Int(a), Bound::Int(b)) => Some(a.cmp(b)),
+            (Bound::Number(a), Bound::Number(b)) => a.partial_cmp(b),
+            (Bound::Int(_), Bound::Number(b)) | (Bound::Number(b), Bound::Int(_)) if b.is_nan() => None,
+            (Bound::Int(a), Bound::Number(b)) => Some(cmp_int_float(*a, *b)),
+            (Bound::Number(a), Bound::Int(b)) => Some(cmp_int_float(*b, *a).reverse()),
+        }
+    }
+}
+
+impl std::fmt::Display for Bound {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Bound::Int(x) => write!(f, "{}", x),
+            Bound::Number(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RangeEnd {
+    Inclusive(Bound),
+    Exclusive(Bound),
+}
+
+// NOTE:  NaN never matches a Number or Range pattern.
 #[derive(Debug, Clone)]
 pub enum Pat {
     Wild,
     Number(f64),
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Char(char),
+    Range(Option<Bound>, Option<RangeEnd>),
     String(String),
     Symbol(String),
-    List(Vec<Pat>, Option<Box<Pat>>),
+    // NOTE:  Prefix patterns, an optional 'rest' pattern, and suffix patterns.  The suffix is always empty when
+    // there is no 'rest' pattern.
+    List(Vec<Pat>, Option<Box<Pat>>, Vec<Pat>),
     Tuple(Vec<Pat>),
     Variable(String),
     At(String, Box<Pat>),
+    Or(Vec<Pat>),
+    StringConcat(Box<Pat>, Box<Pat>),
+    Pin(String),
+    // NOTE:  Keys are constant literals (see compiler::check_pattern) and the map may contain other keys.
+    Map(Vec<(Lit, Pat)>),
 }
 
+// NOTE:  Every expression other than a literal starts with the position of the token that introduces it (the ( of a
+// call, the . of a method call, and so on) so that the type checker can report where it went wrong.
 #[derive(Debug, Clone)]
 pub enum Expr {
     Literal(Lit),
-    Call(Box<Expr>, Vec<Expr>),
+    Call(usize, Box<Expr>, Vec<Expr>),
+    // NOTE:  The parts of an interpolated string like "Hello ${Name}" in order.
+    Interpolate(usize, Vec<Expr>),
+    // NOTE:  X.f(Y) with the receiver, the function name, and the rest of the parameters.
+    Method(usize, Box<Expr>, String, Vec<Expr>),
+    // NOTE:  try { Block } with its catch clauses in order.
+    Try(usize, Box<Top>, Vec<(Pat, Expr)>),
+    // NOTE:  X? unwraps {ok, V} to V and returns {error, E} from the enclosing lambda.
+    Propagate(usize, Box<Expr>),
     // match
 // TODO add if to match cases
 }
 
 #[derive(Debug, Clone)]
 pub struct Let {
+    pub position : usize,
     pub pattern : Pat,
+    pub ann : Option<TypeAnn>,
     pub expr : Expr,
 }
 
+// NOTE:  import "path/to/file.mint" as M;
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub path : String,
+    pub name : String,
+}
+
+// NOTE:  Imports and exports are only allowed at the top of a file, so they are always empty in lambda bodies.  When
+// exports is None every variable the file defines is visible to importers.
 #[derive(Debug, Clone)]
 pub struct Top {
+    pub imports : Vec<Import>,
+    pub exports : Option<Vec<String>>,
     pub lets : Vec<Let>,
     pub expr : Option<Expr>,
 }
 
+// NOTE:  Renders expressions close to how they were written for error messages.
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn all(xs : &[Expr]) -> String {
+            xs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")
+        }
+        match self {
+            Expr::Literal(lit) => write!(f, "{}", lit),
+            Expr::Call(_, func, params) => match (&**func, &params[..]) {
+                (Expr::Literal(Lit::Symbol(op)), [a, b]) if op == "==" => write!(f, "{} == {}", a, b),
+                (func, params) => write!(f, "{}({})", func, all(params)),
+            },
+            Expr::Method(_, receiver, name, params) => write!(f, "{}.{}({})", receiver, name, all(params)),
+            Expr::Try(_, _, _) => write!(f, "try {{ ... }}"),
+            Expr::Propagate(_, expr) => write!(f, "{}?", expr),
+            Expr::Interpolate(_, parts) => {
+                write!(f, "\"")?;
+                for part in parts {
+                    match part {
+                        Expr::Literal(Lit::String(s)) => write!(f, "{}", s.escape_default())?,
+                        e => write!(f, "${{{}}}", e)?,
+                    }
+                }
+                write!(f, "\"")
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Lit {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn all(xs : &[Lit]) -> String {
+            xs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")
+        }
+        match self {
+            Lit::Number(x) => write!(f, "{:?}", x),
+            Lit::Int(x) => write!(f, "{}", x),
+            Lit::BigInt(x) => write!(f, "{}n", x),
+            Lit::Rational(x) => write!(f, "{}r", x),
+            Lit::String(x) => write!(f, "{:?}", x),
+            Lit::Char(x) => write!(f, "{:?}", x),
+            Lit::Symbol(x) => write!(f, "{}", x),
+            Lit::Variable(x) => write!(f, "{}", x),
+            Lit::List(xs) => write!(f, "[{}]", all(xs)),
+            Lit::Tuple(xs) => write!(f, "{{{}}}", all(xs)),
+            Lit::Map(kvps) => write!(f, "%{{{}}}", kvps.iter().map(|(k, v)| format!("{} => {}", k, v)).collect::<Vec<_>>().join(", ")),
+            Lit::Lambda(Lambda { name: Some(name), .. }) => write!(f, "fun {}", name),
+            Lit::Lambda(_) => write!(f, "fun"),
+        }
+    }
+}
+
 use denest::Linearizable;
 
 impl<'a> Linearizable<'a> for Pat {
@@ -92,13 +267,22 @@ impl<'a> Linearizable<'a> for Pat {
         match self {
             Wild => vec![],
             Number(_) => vec![],
+            Int(_) => vec![],
+            BigInt(_) => vec![],
+            Rational(_) => vec![],
+            Range(_, _) => vec![],
             String(_) => vec![],
+            Char(_) => vec![],
             Symbol(_) => vec![],
-            List(ps, Some(mp)) => ps.iter().chain(std::iter::once(&**mp)).collect(),
-            List(ps, None) => ps.iter().collect(),
+            List(ps, Some(mp), ss) => ps.iter().chain(std::iter::once(&**mp)).chain(ss.iter()).collect(),
+            List(ps, None, _) => ps.iter().collect(),
             Tuple(ps) => ps.iter().collect(),
             Variable(_) => vec![],
             At(_, p) => vec![ p ],
+            Or(ps) => ps.iter().collect(),
+            StringConcat(a, b) => vec![ a, b ],
+            Pin(_) => vec![],
+            Map(kvps) => kvps.iter().map(|(_, p)| p).collect(),
         }
     }
 }
@@ -111,15 +295,41 @@ impl Top {
 }
 
 impl Pat { 
-    pub fn variables_to_bind<'a>(&'a self) -> impl Iterator<Item = &'a str> {
+    pub fn variables_to_pin<'a>(&'a self) -> impl Iterator<Item = &'a str> {
         self.to_lax()
-            .filter(|p| matches!(p, Pat::Variable(_) | Pat::At(_, _)))
-            .map(|p| match p {
-                Pat::Variable(x) => x.as_str(),
-                Pat::At(x, _) => x.as_str(),
-                _ => unreachable!(), 
+            .filter_map(|p| match p {
+                Pat::Pin(x) => Some(x.as_str()),
+                _ => None,
             })
     }
+
+    pub fn variables_to_bind<'a>(&'a self) -> impl Iterator<Item = &'a str> {
+        fn bind<'a>(pat : &'a Pat, out : &mut Vec<&'a str>) {
+            match pat {
+                Pat::Variable(x) => out.push(x.as_str()),
+                Pat::At(x, p) => {
+                    out.push(x.as_str());
+                    bind(p, out);
+                },
+                // NOTE:  Every alternative of an or pattern binds the same variables (see compiler::check_pattern),
+                // so only the first alternative is visited.
+                Pat::Or(ps) => {
+                    if let Some(p) = ps.first() {
+                        bind(p, out);
+                    }
+                },
+                p => {
+                    for x in p.l_next() {
+                        bind(x, out);
+                    }
+                },
+            }
+        }
+
+        let mut ret = vec![];
+        bind(self, &mut ret);
+        ret.into_iter()
+    }
 }
 
 
@@ -159,4 +369,16 @@ mod test {
         assert_eq!( output, vec!["X", "Y", "Z", "W", "H", "N"] );
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn bound_variables_should_only_visit_first_or_alternative() {
+        let tokens = crate::parsing::tokenizer::tokenize(
+            "let ({ X, Y } | [X, Y]) = 5;
+             let Z @ (a | b) = 6;
+             ").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = top.variables_to_bind().collect::<Vec<_>>();
+
+        assert_eq!( output, vec!["X", "Y", "Z"] );
+    }
+}
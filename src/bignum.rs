@@ -0,0 +1,651 @@
+
+use std::cmp::Ordering;
+use std::ops::{Add, Sub, Mul, Neg};
+
+// NOTE:  Sign and magnitude where the magnitude is little endian base 2^32 digits without trailing zeros.  Zero has
+// no digits and is never negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative : bool,
+    digits : Vec<u32>,
+}
+
+// NOTE:  Always normalized so that the denominator is positive and shares no factors with the numerator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numer : BigInt,
+    denom : BigInt,
+}
+
+fn trim(digits : &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn cmp_mag(a : &[u32], b : &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a : &[u32], b : &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut ret = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, x) in long.iter().enumerate() {
+        let sum = *x as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        ret.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry != 0 {
+        ret.push(carry as u32);
+    }
+    ret
+}
+
+// NOTE:  Requires a >= b.
+fn sub_mag(a : &[u32], b : &[u32]) -> Vec<u32> {
+    let mut ret = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, x) in a.iter().enumerate() {
+        let mut diff = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        else {
+            borrow = 0;
+        }
+        ret.push(diff as u32);
+    }
+    trim(&mut ret);
+    ret
+}
+
+fn mul_mag(a : &[u32], b : &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut ret = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let t = *x as u64 * *y as u64 + ret[i + j] as u64 + carry;
+            ret[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        ret[i + b.len()] = carry as u32;
+    }
+    trim(&mut ret);
+    ret
+}
+
+fn divrem_small(a : &[u32], d : u32) -> (Vec<u32>, u32) {
+    let mut q = vec![0u32; a.len()];
+    let mut r = 0u64;
+    for i in (0..a.len()).rev() {
+        let cur = (r << 32) | a[i] as u64;
+        q[i] = (cur / d as u64) as u32;
+        r = cur % d as u64;
+    }
+    trim(&mut q);
+    (q, r as u32)
+}
+
+// NOTE:  Shifts left by fewer than 32 bits and always adds a top digit for the bits shifted out (which may be zero).
+fn shl_bits(digits : &[u32], shift : u32) -> Vec<u32> {
+    let mut ret = Vec::with_capacity(digits.len() + 1);
+    let mut carry = 0;
+    for d in digits {
+        ret.push((d << shift) | carry);
+        carry = if shift == 0 { 0 } else { d >> (32 - shift) };
+    }
+    ret.push(carry);
+    ret
+}
+
+// NOTE:  Requires b to be non zero.  Multi digit divisors use Knuth's algorithm D (The Art of Computer Programming,
+// 4.3.1), which finds a whole digit of the quotient at a time.  Both numbers are shifted so that the top digit of the
+// divisor has its high bit set, which keeps each estimated quotient digit at most two too large.
+fn divrem_mag(a : &[u32], b : &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if b.len() == 1 {
+        let (q, r) = divrem_small(a, b[0]);
+        return (q, if r == 0 { vec![] } else { vec![r] });
+    }
+    const BASE : u64 = 1 << 32;
+    let shift = b[b.len() - 1].leading_zeros();
+    let mut v = shl_bits(b, shift);
+    v.pop();
+    let mut u = shl_bits(a, shift);
+    let n = v.len();
+    let m = a.len() - n;
+    let mut q = vec![0u32; m + 1];
+
+    for j in (0..=m).rev() {
+        let top = ((u[j + n] as u64) << 32) | u[j + n - 1] as u64;
+        let mut qhat = top / v[n - 1] as u64;
+        let mut rhat = top % v[n - 1] as u64;
+        while qhat >= BASE || qhat * v[n - 2] as u64 > ((rhat << 32) | u[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += v[n - 1] as u64;
+            if rhat >= BASE {
+                break;
+            }
+        }
+
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let p = qhat * v[i] as u64 + carry;
+            carry = p >> 32;
+            let t = u[i + j] as i64 - borrow - (p & 0xffff_ffff) as i64;
+            u[i + j] = t as u32;
+            borrow = (t < 0) as i64;
+        }
+        let t = u[j + n] as i64 - borrow - carry as i64;
+        u[j + n] = t as u32;
+
+        // NOTE:  qhat was still one too large, so the divisor is added back.
+        if t < 0 {
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+        q[j] = qhat as u32;
+    }
+
+    let mut r = (0..n).map(|i| {
+        if shift == 0 {
+            u[i]
+        }
+        else {
+            (u[i] >> shift) | (u[i + 1] << (32 - shift))
+        }
+    }).collect::<Vec<_>>();
+    trim(&mut q);
+    trim(&mut r);
+    (q, r)
+}
+
+fn bit_len(digits : &[u32]) -> usize {
+    match digits.last() {
+        Some(d) => digits.len() * 32 - d.leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+// NOTE:  x * 2^e without overflowing or underflowing the power of two on the way.
+fn scale(x : f64, e : i64) -> f64 {
+    let e = e.clamp(-2200, 2200) as i32;
+    x * 2f64.powi(e / 2) * 2f64.powi(e - e / 2)
+}
+
+// NOTE:  Rounds to the nearest f64 with ties to even.  sticky says whether there are non zero bits below the
+// magnitude, which only matters for breaking ties and is only used for magnitudes wider than 64 bits.
+fn mag_to_f64(digits : &[u32], sticky : bool) -> f64 {
+    let len = bit_len(digits);
+    if len <= 64 {
+        return digits.iter().rev().fold(0u64, |acc, d| (acc << 32) | *d as u64) as f64;
+    }
+    // NOTE:  The top 64 bits with every bit below them folded into the lowest bit, so that converting them to f64
+    // rounds the same way the whole magnitude would.
+    let shift = len - 64;
+    let (word, bit) = (shift / 32, shift % 32);
+    let window = (0..3).rev().fold(0u128, |acc, i| (acc << 32) | *digits.get(word + i).unwrap_or(&0) as u128);
+    let dropped = sticky || digits[..word].iter().any(|d| *d != 0) || digits[word] & ((1u64 << bit) - 1) as u32 != 0;
+    scale(((window >> bit) as u64 | dropped as u64) as f64, shift as i64)
+}
+
+impl BigInt {
+    fn from_mag(negative : bool, mut digits : Vec<u32>) -> BigInt {
+        trim(&mut digits);
+        BigInt { negative: negative && !digits.is_empty(), digits }
+    }
+
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, digits: vec![] }
+    }
+
+    pub fn from_i64(x : i64) -> BigInt {
+        let mag = x.unsigned_abs();
+        BigInt::from_mag(x < 0, vec![mag as u32, (mag >> 32) as u32])
+    }
+
+    pub fn pow2(n : usize) -> BigInt {
+        let mut digits = vec![0u32; n / 32];
+        digits.push(1 << (n % 32));
+        BigInt::from_mag(false, digits)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, digits: self.digits.clone() }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let mag = self.digits.iter().rev().fold(0u64, |acc, d| (acc << 32) | *d as u64) as i128;
+        let x = if self.negative { -mag } else { mag };
+        i64::try_from(x).ok()
+    }
+
+    // NOTE:  The nearest f64, or infinity when it is too large.
+    pub fn to_f64(&self) -> f64 {
+        let mag = mag_to_f64(&self.digits, false);
+        if self.negative { -mag } else { mag }
+    }
+
+    // NOTE:  Truncating division where the remainder has the same sign as the dividend.  Returns None when dividing
+    // by zero.
+    pub fn checked_div_rem(&self, other : &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = divrem_mag(&self.digits, &other.digits);
+        Some((BigInt::from_mag(self.negative != other.negative, q), BigInt::from_mag(self.negative, r)))
+    }
+
+    pub fn gcd(&self, other : &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, r) = a.checked_div_rem(&b).expect("b is not zero");
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    // NOTE:  An optional sign followed by decimal digits.
+    pub fn parse(s : &str) -> Option<BigInt> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let mut mag = vec![];
+        for c in digits.chars() {
+            mag = add_mag(&mul_mag(&mag, &[10]), &[c.to_digit(10).expect("ascii digit")]);
+            trim(&mut mag);
+        }
+        Some(BigInt::from_mag(negative, mag))
+    }
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = vec![];
+        let mut mag = self.digits.clone();
+        while !mag.is_empty() {
+            let (q, r) = divrem_small(&mag, 1_000_000_000);
+            chunks.push(r);
+            mag = q;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().expect("non zero has a chunk"))?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other : &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.digits, &other.digits),
+            (true, true) => cmp_mag(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other : &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_mag(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other : &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_mag(self.negative, add_mag(&self.digits, &other.digits));
+        }
+        match cmp_mag(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::from_mag(other.negative, sub_mag(&other.digits, &self.digits)),
+            _ => BigInt::from_mag(self.negative, sub_mag(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other : &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other : &BigInt) -> BigInt {
+        BigInt::from_mag(self.negative != other.negative, mul_mag(&self.digits, &other.digits))
+    }
+}
+
+impl Rational {
+    pub fn new(numer : BigInt, denom : BigInt) -> Option<Rational> {
+        if denom.is_zero() {
+            return None;
+        }
+        let g = numer.gcd(&denom);
+        let (mut numer, _) = numer.checked_div_rem(&g)?;
+        let (mut denom, _) = denom.checked_div_rem(&g)?;
+        if denom.is_negative() {
+            numer = -&numer;
+            denom = -&denom;
+        }
+        Some(Rational { numer, denom })
+    }
+
+    pub fn from_bigint(x : BigInt) -> Rational {
+        Rational { numer: x, denom: BigInt::from_i64(1) }
+    }
+
+    // NOTE:  Every finite f64 is exactly a (possibly very large) rational.  Returns None for NaN and infinity.
+    pub fn from_f64(x : f64) -> Option<Rational> {
+        if !x.is_finite() {
+            return None;
+        }
+        let bits = x.to_bits();
+        let exp = ((bits >> 52) & 0x7ff) as i64;
+        let frac = (bits & ((1 << 52) - 1)) as i64;
+        let (mantissa, e) = if exp == 0 { (frac, -1074) } else { (frac | (1 << 52), exp - 1075) };
+        let mantissa = BigInt::from_i64(if x.is_sign_negative() { -mantissa } else { mantissa });
+        if e >= 0 {
+            Some(Rational::from_bigint(&mantissa * &BigInt::pow2(e as usize)))
+        }
+        else {
+            Rational::new(mantissa, BigInt::pow2((-e) as usize))
+        }
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    // NOTE:  Rounds toward zero.
+    pub fn trunc(&self) -> BigInt {
+        self.numer.checked_div_rem(&self.denom).expect("denominator is not zero").0
+    }
+
+    // NOTE:  The quotient is found with at least 65 bits and a sticky bit for the remainder, so it rounds to the
+    // nearest f64.  Results below f64::MIN_POSITIVE can be rounded twice.
+    pub fn to_f64(&self) -> f64 {
+        let (n, d) = (&self.numer.digits, &self.denom.digits);
+        if n.is_empty() {
+            return 0.0;
+        }
+        let k = 65 + bit_len(d) as i64 - bit_len(n) as i64;
+        let (q, r) = if k >= 0 {
+            divrem_mag(&mul_mag(n, &BigInt::pow2(k as usize).digits), d)
+        }
+        else {
+            divrem_mag(n, &mul_mag(d, &BigInt::pow2((-k) as usize).digits))
+        };
+        let mag = scale(mag_to_f64(&q, !r.is_empty()), -k);
+        if self.numer.negative { -mag } else { mag }
+    }
+
+    pub fn checked_div(&self, other : &Rational) -> Option<Rational> {
+        Rational::new(&self.numer * &other.denom, &self.denom * &other.numer)
+    }
+
+    // NOTE:  The remainder of truncating division, so it has the same sign as the dividend.
+    pub fn checked_rem(&self, other : &Rational) -> Option<Rational> {
+        let q = Rational::from_bigint(self.checked_div(other)?.trunc());
+        Some(self - &(other * &q))
+    }
+
+    // NOTE:  An optional sign, decimal digits with an optional fraction, and an optional denominator like -1.5/7.
+    pub fn parse(s : &str) -> Option<Rational> {
+        let (decimal, denom) = match s.split_once('/') {
+            Some((decimal, denom)) => (decimal, BigInt::parse(denom)?),
+            None => (s, BigInt::from_i64(1)),
+        };
+        let (whole, frac) = decimal.split_once('.').unwrap_or((decimal, ""));
+        if !frac.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let numer = BigInt::parse(&format!("{}{}", whole, frac))?;
+        let scale = BigInt::parse(&format!("1{}", "0".repeat(frac.len())))?;
+        Rational::new(numer, &denom * &scale)
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.denom == BigInt::from_i64(1) {
+            write!(f, "{}", self.numer)
+        }
+        else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other : &Rational) -> Ordering {
+        (&self.numer * &other.denom).cmp(&(&other.numer * &self.denom))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other : &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational { numer: -&self.numer, denom: self.denom.clone() }
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+    fn add(self, other : &Rational) -> Rational {
+        Rational::new( &(&self.numer * &other.denom) + &(&other.numer * &self.denom)
+                     , &self.denom * &other.denom
+                     ).expect("product of non zero denominators is not zero")
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+    fn sub(self, other : &Rational) -> Rational {
+        self + &(-other)
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+    fn mul(self, other : &Rational) -> Rational {
+        Rational::new(&self.numer * &other.numer, &self.denom * &other.denom)
+            .expect("product of non zero denominators is not zero")
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn big(s : &str) -> BigInt {
+        BigInt::parse(s).unwrap()
+    }
+
+    fn ratio(s : &str) -> Rational {
+        Rational::parse(s).unwrap()
+    }
+
+    #[test]
+    fn bigint_should_round_trip_through_string() {
+        for s in ["0", "-1", "4294967296", "-123456789012345678901234567890", "1000000000000000000"] {
+            assert_eq!( big(s).to_string(), s );
+        }
+        assert_eq!( big("+007").to_string(), "7" );
+        assert_eq!( big("-0").to_string(), "0" );
+        assert!( BigInt::parse("12a").is_none() );
+        assert!( BigInt::parse("-").is_none() );
+    }
+
+    #[test]
+    fn bigint_should_do_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+
+        assert_eq!( (&a + &b).to_string(), "-864197532086419753208641975320" );
+        assert_eq!( (&a - &b).to_string(), "1111111110111111111011111111100" );
+        assert_eq!( (&a * &b).to_string(), "-121932631137021795226185032733622923332237463801111263526900" );
+    }
+
+    #[test]
+    fn bigint_division_should_truncate() {
+        let (q, r) = big("-100000000000000000000007").checked_div_rem(&big("10000000000000")).unwrap();
+
+        assert_eq!( q.to_string(), "-10000000000" );
+        assert_eq!( r.to_string(), "-7" );
+        assert!( big("1").checked_div_rem(&BigInt::zero()).is_none() );
+    }
+
+    #[test]
+    fn bigint_division_should_match_multiplication() {
+        let xs = [ big("340282366920938463463374607431768211455")
+                 , big("79228162514264337589248983040")
+                 , big("18446744073709551616")
+                 , big("123456789012345678901234567890123456789")
+                 , big("170141183460469231731687303715884105729")
+                 , big("4294967297")
+                 ];
+        for a in &xs {
+            for b in &xs {
+                let (q, r) = a.checked_div_rem(b).unwrap();
+
+                assert_eq!( &(&q * b) + &r, *a );
+                assert_eq!( cmp_mag(&r.digits, &b.digits), Ordering::Less );
+            }
+        }
+    }
+
+    #[test]
+    fn bigint_division_should_add_back_when_estimate_is_too_large() {
+        let a = BigInt::from_mag(false, vec![0, 0, 0x8000_0000, 0x7fff_ffff]);
+        let b = BigInt::from_mag(false, vec![1, 0, 0x8000_0000]);
+
+        let (q, r) = a.checked_div_rem(&b).unwrap();
+
+        assert_eq!( &(&q * &b) + &r, a );
+        assert_eq!( cmp_mag(&r.digits, &b.digits), Ordering::Less );
+    }
+
+    #[test]
+    fn to_f64_should_round_to_nearest() {
+        let x = &(&BigInt::pow2(96) + &BigInt::pow2(43)) + &BigInt::from_i64(1);
+
+        assert_eq!( x.to_f64(), 2f64.powi(96) + 2f64.powi(44) );
+        assert_eq!( big("9007199254740993").to_f64(), 9007199254740992.0 );
+        assert_eq!( (-&big("9007199254740995")).to_f64(), -9007199254740996.0 );
+        assert_eq!( ratio("1/3").to_f64(), 1.0 / 3.0 );
+        assert_eq!( ratio("-0.1").to_f64(), -0.1 );
+    }
+
+    #[test]
+    fn rational_to_f64_should_handle_large_parts() {
+        let x = Rational::new(&BigInt::pow2(2000) + &BigInt::from_i64(1), BigInt::pow2(1999)).unwrap();
+
+        assert_eq!( x.to_f64(), 2.0 );
+        assert_eq!( Rational::new(BigInt::from_i64(1), BigInt::pow2(2000)).unwrap().to_f64(), 0.0 );
+        assert_eq!( Rational::from_bigint(BigInt::pow2(2000)).to_f64(), f64::INFINITY );
+    }
+
+    #[test]
+    fn bigint_should_convert_to_i64_when_it_fits() {
+        assert_eq!( BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN) );
+        assert_eq!( BigInt::from_i64(i64::MAX).to_i64(), Some(i64::MAX) );
+        assert_eq!( big("9223372036854775808").to_i64(), None );
+    }
+
+    #[test]
+    fn rational_should_normalize() {
+        let x = Rational::new(BigInt::from_i64(6), BigInt::from_i64(-4)).unwrap();
+
+        assert_eq!( x.to_string(), "-3/2" );
+        assert_eq!( ratio("1.25").to_string(), "5/4" );
+        assert_eq!( ratio("-0.5/3").to_string(), "-1/6" );
+        assert_eq!( ratio("4/2").to_string(), "2" );
+        assert!( Rational::parse("1/0").is_none() );
+    }
+
+    #[test]
+    fn rational_should_do_exact_arithmetic() {
+        let tenth = ratio("0.1");
+        let sum = &(&tenth + &tenth) + &tenth;
+
+        assert_eq!( sum, ratio("3/10") );
+        assert_eq!( (&ratio("1/3") * &ratio("3/4")).to_string(), "1/4" );
+        assert_eq!( ratio("7/2").checked_rem(&ratio("1")).unwrap().to_string(), "1/2" );
+        assert_eq!( ratio("-7/2").trunc().to_string(), "-3" );
+        assert!( ratio("1").checked_div(&ratio("0")).is_none() );
+    }
+
+    #[test]
+    fn rational_from_f64_should_be_exact() {
+        assert_eq!( Rational::from_f64(0.5).unwrap().to_string(), "1/2" );
+        assert_eq!( Rational::from_f64(-3.0).unwrap().to_string(), "-3" );
+        assert_eq!( Rational::from_f64(0.1).unwrap().to_string(), "3602879701896397/36028797018963968" );
+        assert!( Rational::from_f64(f64::NAN).is_none() );
+    }
+
+    #[test]
+    fn rational_should_order_by_value() {
+        assert!( ratio("1/3") < ratio("0.34") );
+        assert!( ratio("-1/2") < ratio("-1/3") );
+    }
+}
@@ -5,27 +5,50 @@ use denest::Linearizable;
 
 use crate::runtime::*;
 use crate::ast::*;
+use crate::natives::Natives;
+use crate::evaling::pattern_matcher;
+use crate::typing::annotation;
+use crate::typing::types::Type;
 
 use super::error::*;
 use super::instr;
+use super::lambdas::Compiled;
+use super::trace::{Frame, SourceMap};
 
 
 type I = Instr<RuntimeData, Heap>;
-type Fs = HashMap<Func, Vec<I>>; 
 type M = HashMap<String, HeapAddress>;
 
+// NOTE:  locals are the variables of the lambda being compiled, in_lambda says whether ? has a lambda to return
+// from, and contracts says whether requires and ensures clauses are compiled.  source gives lambdas and calls their
+// lines for tracebacks.
 struct C {
     sym_count : usize,
     label_count : usize,
     func_count : usize,
+    locals : HashMap<String, Symbol>,
+    in_lambda : bool,
+    contracts : bool,
+    source : SourceMap,
 }
 
 impl C {
-    fn new(func_count : usize) -> Self {
-        C { sym_count: 0, label_count: 0, func_count }
+    fn new(func_count : usize, contracts : bool, source : SourceMap) -> Self {
+        C { sym_count: 0, label_count: 0, func_count, locals: HashMap::new(), in_lambda: false, contracts, source }
     }
     fn fresh(&self) -> Self {
-        C { sym_count: 0, label_count: 0, func_count: self.func_count }
+        C { sym_count: 0
+          , label_count: 0
+          , func_count: self.func_count
+          , locals: HashMap::new()
+          , in_lambda: self.in_lambda
+          , contracts: self.contracts
+          , source: self.source.clone()
+          }
+    }
+    // NOTE:  Takes back the functions compiled by a context made with fresh.
+    fn absorb(&mut self, inner : C) {
+        self.func_count = inner.func_count;
     }
     fn symbol(&mut self) -> Symbol {
         self.sym_count += 1;
@@ -41,26 +64,126 @@ impl C {
     }
 }
 
-pub fn compile(input : &Expr, address_map : &M, functions : &mut Fs) -> Result<Vec<I>, StaticError> {
-    let mut func = functions.keys().map(|k| k.0).max().unwrap_or(0);
-    let mut c = C::new(func);
+pub fn compile(input : &Expr, address_map : &M, natives : &Natives, symbols : &mut SymbolTable, contracts : bool, source : &SourceMap) -> Result<Vec<I>, StaticError> {
+    let mut c = C::new(natives.lambdas().last_func(), contracts, source.clone());
+    let (sym, mut prog) = compile_expr(&mut c, input, address_map, natives, symbols)?;
+    let mut x = vec![ Instr::Return(sym) ];
+    prog.append(&mut x);
+    Ok(prog)
+}
+
+fn compile_expr(c : &mut C, input : &Expr, address_map : &M, natives : &Natives, symbols : &mut SymbolTable) -> Result<(Symbol, Vec<I>), StaticError> {
     match input {
-        Expr::Literal(lit) => {
-            let (sym, mut prog) = compile_literal(&mut c, &lit, address_map, functions)?;
-            let mut x = vec![ Instr::Return(sym) ];
-            prog.append(&mut x);
-            Ok(prog)
+        Expr::Literal(lit) => compile_literal(c, lit, address_map, natives, symbols),
+        Expr::Call(position, func_expr, params) => {
+            // NOTE:  Calling a symbol calls the native with that name, so a literal symbol can be checked ahead of time.
+            if let Expr::Literal(Lit::Symbol(name)) = &**func_expr {
+                if !natives.contains(name) {
+                    return Err(StaticError::FunctionNotDefined(name.into()));
+                }
+            }
+
+            let (func_sym, mut prog) = compile_expr(c, func_expr, address_map, natives, symbols)?;
+
+            let mut param_syms = vec![];
+            for param in params {
+                let (param_sym, mut param_prog) = compile_expr(c, param, address_map, natives, symbols)?;
+                prog.append(&mut param_prog);
+                param_syms.push(param_sym);
+            }
+
+            let ret = c.symbol();
+            prog.push(instr::call(func_sym, param_syms, ret, natives.clone(), c.source.line(*position)));
+            Ok((ret, prog))
+        },
+        // NOTE:  There are no records yet so X.f(Y) always resolves to the function f(X, Y).  Once records exist, a
+        // field named f takes precedence and this is where that check goes.
+        Expr::Method(start, receiver, name, params) => {
+            let mut params = params.clone();
+            params.insert(0, (**receiver).clone());
+            let call = Expr::Call(*start, Box::new(Expr::Literal(Lit::Symbol(name.clone()))), params);
+            compile_expr(c, &call, address_map, natives, symbols)
+        },
+        // NOTE:  The try block and each handler are separate programs that are run by the try_catch instruction, so
+        // that a raise inside of them can be caught.  The locals they use are passed in through a shared cell and
+        // loaded at the start of each program.
+        Expr::Try(_, block, handlers) => {
+            let captured = captured_locals(c);
+            let cell = instr::Cell::default();
+
+            let mut inner = c.fresh();
+            let mut block_prog = load_from_cell(&mut inner, &cell, captured.iter().map(|(var, _)| var));
+            let (ret, mut prog) = compile_block(&mut inner, block, "try", address_map, natives, symbols)?;
+            block_prog.append(&mut prog);
+            block_prog.push(Instr::Return(ret));
+            c.absorb(inner);
+
+            let mut outer_prog = vec![];
+            let mut compiled_handlers = vec![];
+            for (pattern, expr) in handlers {
+                check_pattern(pattern)?;
+                let locals = c.locals.clone();
+                let pins = pin_symbols(c, pattern, &locals, address_map, &mut outer_prog)?;
+
+                let mut inner = c.fresh();
+                let vars = pattern.variables_to_bind().map(|v| v.to_string()).collect::<Vec<_>>();
+                let mut handler_prog = load_from_cell(&mut inner, &cell, captured.iter().map(|(var, _)| var).chain(vars.iter()));
+                let (ret, mut prog) = compile_expr(&mut inner, expr, address_map, natives, symbols)?;
+                handler_prog.append(&mut prog);
+                handler_prog.push(Instr::Return(ret));
+                c.absorb(inner);
+
+                compiled_handlers.push(instr::Handler { pattern: pattern_matcher::intern(pattern, symbols)?, vars, pins, prog: handler_prog });
+            }
+
+            let result = c.symbol();
+            let captured_syms = captured.into_iter().map(|(_, sym)| sym).collect();
+            outer_prog.push(instr::try_catch(captured_syms, cell, block_prog, compiled_handlers, result));
+            Ok((result, outer_prog))
+        },
+        Expr::Propagate(_, expr) => {
+            if !c.in_lambda {
+                return Err(StaticError::PropagateOutsideLambda(input.to_string()));
+            }
+            let (data, mut prog) = compile_expr(c, expr, address_map, natives, symbols)?;
+            let result = c.symbol();
+            prog.push(instr::propagate(data, result));
+            Ok((result, prog))
+        },
+        // NOTE:  Interpolation is lowered to concat with every embedded expression converted by to_string.
+        Expr::Interpolate(start, parts) => {
+            let params = parts.iter().map(|part| match part {
+                Expr::Literal(Lit::String(_)) => part.clone(),
+                _ => Expr::Call(*start, Box::new(Expr::Literal(Lit::Symbol("to_string".into()))), vec![part.clone()]),
+            }).collect();
+            let call = Expr::Call(*start, Box::new(Expr::Literal(Lit::Symbol("concat".into()))), params);
+            compile_expr(c, &call, address_map, natives, symbols)
         },
-        Expr::Call(func_expr, params) => Err(StaticError::Todo),
     }
 }
 
-fn compile_literal(c : &mut C, input : &Lit, address_map : &M, functions : &mut Fs) -> Result<(Symbol, Vec<I>), StaticError> {
+fn compile_literal(c : &mut C, input : &Lit, address_map : &M, natives : &Natives, symbols : &mut SymbolTable) -> Result<(Symbol, Vec<I>), StaticError> {
     fn single( s : Symbol, i : Instr<RuntimeData, Heap> ) -> Result<(Symbol, Vec<I>), StaticError> {
         Ok((s, vec![ i ]))
     }
 
     match input {
+        Lit::Int(x) => { 
+            let s = c.symbol();
+            single(s, Instr::LoadValue(s, RuntimeData::Int(*x)))
+        },
+        Lit::BigInt(x) => { 
+            let s = c.symbol();
+            single(s, Instr::LoadValue(s, RuntimeData::BigInt(x.clone())))
+        },
+        Lit::Rational(x) => { 
+            let s = c.symbol();
+            single(s, Instr::LoadValue(s, RuntimeData::Rational(x.clone())))
+        },
+        Lit::Char(x) => { 
+            let s = c.symbol();
+            single(s, Instr::LoadValue(s, RuntimeData::Char(*x)))
+        },
         Lit::Number(x) => { 
             let s = c.symbol();
             single(s, Instr::LoadValue(s, RuntimeData::Number(*x)))
@@ -71,8 +194,9 @@ fn compile_literal(c : &mut C, input : &Lit, address_map : &M, functions : &mut
         },
         Lit::Symbol(x) => {
             let s = c.symbol();
-            single(s, Instr::LoadValue(s, RuntimeData::Symbol(x.to_string())))
+            single(s, Instr::LoadValue(s, RuntimeData::Symbol(symbols.intern(x))))
         },
+        Lit::Variable(x) if c.locals.contains_key(x) => Ok((c.locals[x], vec![])),
         Lit::Variable(x) if !address_map.contains_key(x) => Err(StaticError::VariableNotDefined(x.into())), 
         Lit::Variable(x) => {
             let address = address_map.get(x).unwrap().clone();
@@ -80,7 +204,7 @@ fn compile_literal(c : &mut C, input : &Lit, address_map : &M, functions : &mut
             single(s, instr::load_from_heap(address, s))
         }, 
         Lit::List(x) => {
-            let y = x.iter().map(|d| compile_literal(c, d, address_map, functions)).collect::<Result<Vec<_>, _>>()?;
+            let y = x.iter().map(|d| compile_literal(c, d, address_map, natives, symbols)).collect::<Result<Vec<_>, _>>()?;
             let ret_sym = c.symbol();
             let ret_address = c.symbol();
             let mut ret : Vec<I> = vec![ Instr::LoadValue(ret_sym, RuntimeData::List(vec![])) ];
@@ -101,7 +225,7 @@ fn compile_literal(c : &mut C, input : &Lit, address_map : &M, functions : &mut
             Ok((ret_address, ret))
         },
         Lit::Tuple(x) => {
-            let y = x.iter().map(|d| compile_literal(c, d, address_map, functions)).collect::<Result<Vec<_>, _>>()?;
+            let y = x.iter().map(|d| compile_literal(c, d, address_map, natives, symbols)).collect::<Result<Vec<_>, _>>()?;
             let ret_sym = c.symbol();
             let ret_address = c.symbol();
             let mut ret : Vec<I> = vec![ Instr::LoadValue(ret_sym, RuntimeData::Tuple(vec![])) ];
@@ -121,50 +245,296 @@ fn compile_literal(c : &mut C, input : &Lit, address_map : &M, functions : &mut
 
             Ok((ret_address, ret))
         },
+        Lit::Map(x) => {
+            let ret_sym = c.symbol();
+            let ret_address = c.symbol();
+            let mut ret : Vec<I> = vec![ Instr::LoadValue(ret_sym, RuntimeData::Map(vec![])) ];
+
+            ret.push(instr::insert_into_heap(ret_sym, ret_address));
+
+            for (key, value) in x {
+                let (key_sym, mut key_prog) = compile_literal(c, key, address_map, natives, symbols)?;
+                let (value_sym, mut value_prog) = compile_literal(c, value, address_map, natives, symbols)?;
+                ret.append(&mut key_prog);
+                ret.append(&mut value_prog);
+                ret.push(instr::put_into_map_in_heap(key_sym, value_sym, ret_address));
+            }
+
+            Ok((ret_address, ret))
+        },
         Lit::Lambda(x) => {
             lambda_variables_are_unique(&x)?;
+            for pattern in x.params.iter().chain(x.body.lets.iter().map(|l| &l.pattern)) {
+                check_pattern(pattern)?;
+            }
             
             let mut func_body : Vec<I> = vec![];
 
             let mut var_to_sym : HashMap<String, Symbol> = HashMap::new();
 
-            // TODO use this map to figure out what symbol a variable needs to grab
-            /*let mut var_to_sym = x.params.iter().flat_map(|param| param.variables_to_bind())
-                                                .map(|var| (var, c.symbol()))
-                                                .collect::<Vec<_>>()
-                                                .into_iter()
-                                                .chain(x.body.variables_to_bind().map(|var| (var, c.symbol())))
-                                                .collect::<HashMap<&str, Symbol>>();*/
-
+            // NOTE:  The caller puts the parameters into the cell (see Lambdas::call).
+            let cell = instr::Cell::default();
             let mut pre_datas = vec![];
-            for _ in 0..x.params.len() {
+            for index in 0..x.params.len() {
                 let s = c.symbol();
                 pre_datas.push(s);
-                func_body.push(Instr::PopParam(s));
+                func_body.push(instr::load_from_cell(cell.clone(), index, s));
+            }
+
+            // NOTE:  Annotated parameters are checked at function entry before they are matched.  The return
+            // annotation is checked before returning.
+            let mut ann_vars = HashMap::new();
+            let mut next_var = 0;
+            let mut ann_to_type = |ann : &TypeAnn| annotation::to_type(ann, x.position, &mut ann_vars, &mut |_| { next_var += 1; Type::Var(next_var - 1) })
+                                                       .map_err(|e| StaticError::InvalidTypeAnnotation(c.source.locate(Box::new(e), x.position).to_string()));
+            for (ann, pre_data) in std::iter::zip(&x.param_anns, &pre_datas) {
+                if let Some(ann) = ann {
+                    func_body.push(instr::check_type(*pre_data, ann_to_type(ann)?));
+                }
             }
-            pre_datas.reverse();
+            let ret_type = match &x.ret_ann {
+                Some(ann) => Some(ann_to_type(ann)?),
+                None => None,
+            };
 
             for (param, pre_data) in std::iter::zip(&x.params, pre_datas) {
                 let result = c.symbol();
                 let local_var_to_sym = param.variables_to_bind().map(|var| (var.to_string(), c.symbol())).collect::<HashMap<String, Symbol>>();
-                func_body.push(instr::pattern_match(pre_data, param.clone(), result, local_var_to_sym.clone()));
+
+                // NOTE:  A pin can refer to a variable bound by an earlier parameter or to a global.
+                let pin_to_sym = pin_symbols(c, param, &var_to_sym, address_map, &mut func_body)?;
+
+                func_body.push(instr::pattern_match(pre_data, pattern_matcher::intern(param, symbols)?, result, local_var_to_sym.clone(), pin_to_sym));
                 func_body.push(instr::panic_on_false(result, "".into()));
                 for (k, v) in local_var_to_sym.into_iter() {
                     var_to_sym.insert(k, v);
                 }
             }
 
+            // NOTE:  Lambdas don't capture, so the body only sees its own locals and the globals.
+            let outer_locals = std::mem::replace(&mut c.locals, var_to_sym);
+            let outer_in_lambda = std::mem::replace(&mut c.in_lambda, true);
+            let body = compile_lambda_body(c, x, ret_type, address_map, natives, symbols);
+            c.locals = outer_locals;
+            c.in_lambda = outer_in_lambda;
+            func_body.append(&mut body?);
+
             let func_address = c.func();
+            let frame = lambda_frame(c, x);
 
-            functions.insert(func_address, func_body);
+            let name = x.name.clone().unwrap_or_else(|| "lambda".into());
+            natives.lambdas().insert(func_address, Compiled { name, arity: x.params.len(), cell, prog: HashMap::from([(Func(0), func_body)]), frame });
 
             let func_address_sym = c.symbol();
 
-            // TODO compile body
+            single(func_address_sym, Instr::LoadValue(func_address_sym, RuntimeData::Function(func_address)))
+        },
+    }
+}
+
+// NOTE:  Compiles the lets and the final expression of a lambda body or a try block, binding the let variables in
+// c.locals.  A block without a final expression results in the empty tuple.
+fn compile_block( c : &mut C, block : &Top, name : &str, address_map : &M, natives : &Natives, symbols : &mut SymbolTable ) -> Result<(Symbol, Vec<I>), StaticError> {
+    let mut prog = vec![];
+
+    for l in &block.lets {
+        check_pattern(&l.pattern)?;
+        let (data, mut expr_prog) = compile_expr(c, &l.expr, address_map, natives, symbols)?;
+        prog.append(&mut expr_prog);
+
+        let result = c.symbol();
+        let local_var_to_sym = l.pattern.variables_to_bind().map(|var| (var.to_string(), c.symbol())).collect::<HashMap<String, Symbol>>();
+        let locals = c.locals.clone();
+        let pin_to_sym = pin_symbols(c, &l.pattern, &locals, address_map, &mut prog)?;
 
-            single(func_address_sym, Instr::LoadFunc(func_address_sym, func_address))
+        prog.push(instr::pattern_match(data, pattern_matcher::intern(&l.pattern, symbols)?, result, local_var_to_sym.clone(), pin_to_sym));
+        prog.push(instr::panic_on_false(result, format!("pattern match failed in {}", name)));
+        c.locals.extend(local_var_to_sym);
+    }
+
+    let ret = match &block.expr {
+        Some(expr) => {
+            let (ret, mut expr_prog) = compile_expr(c, expr, address_map, natives, symbols)?;
+            prog.append(&mut expr_prog);
+            ret
+        },
+        None => {
+            let ret = c.symbol();
+            prog.push(Instr::LoadValue(ret, RuntimeData::Tuple(vec![])));
+            ret
         },
+    };
+
+    Ok((ret, prog))
+}
+
+fn lambda_frame( c : &C, lambda : &Lambda ) -> Frame {
+    let name = match &lambda.name {
+        Some(name) => format!("fun {}", name),
+        None => "fun".into(),
+    };
+    c.source.frame(name, lambda.position)
+}
+
+// NOTE:  The locals in a stable order so that they can be passed to another program through a cell.
+fn captured_locals( c : &C ) -> Vec<(String, Symbol)> {
+    let mut captured = c.locals.iter().map(|(k, v)| (k.clone(), *v)).collect::<Vec<_>>();
+    captured.sort_by(|a, b| a.0.cmp(&b.0));
+    captured
+}
+
+// NOTE:  Loads the values in the cell into new locals with the given names in order.
+fn load_from_cell<'a>( c : &mut C, cell : &instr::Cell, vars : impl Iterator<Item = &'a String> ) -> Vec<I> {
+    let mut prog = vec![];
+    for (index, var) in vars.enumerate() {
+        let sym = c.symbol();
+        prog.push(instr::load_from_cell(cell.clone(), index, sym));
+        c.locals.insert(var.clone(), sym);
+    }
+    prog
+}
+
+// NOTE:  Whether a ? in the block returns from it.  A ? inside of a lambda in the block returns from that lambda.
+fn propagates( block : &Top ) -> bool {
+    fn expr( e : &Expr ) -> bool {
+        match e {
+            Expr::Propagate(_, _) => true,
+            Expr::Literal(_) => false,
+            Expr::Call(_, func, params) => expr(func) || params.iter().any(expr),
+            Expr::Method(_, receiver, _, params) => expr(receiver) || params.iter().any(expr),
+            Expr::Interpolate(_, parts) => parts.iter().any(expr),
+            Expr::Try(_, block, handlers) => propagates(block) || handlers.iter().any(|(_, e)| expr(e)),
+        }
+    }
+    block.lets.iter().any(|l| expr(&l.expr)) || block.expr.iter().any(expr)
+}
+
+fn pin_symbols( c : &mut C, pattern : &Pat, locals : &HashMap<String, Symbol>, address_map : &M, prog : &mut Vec<I> ) -> Result<HashMap<String, Symbol>, StaticError> {
+    let mut pin_to_sym = HashMap::new();
+    for var in pattern.variables_to_pin() {
+        if let Some(sym) = locals.get(var) {
+            pin_to_sym.insert(var.to_string(), *sym);
+        }
+        else if let Some(address) = address_map.get(var) {
+            let sym = c.symbol();
+            prog.push(instr::load_from_heap(*address, sym));
+            pin_to_sym.insert(var.to_string(), sym);
+        }
+        else {
+            return Err(StaticError::VariableNotDefined(var.into()));
+        }
     }
+    Ok(pin_to_sym)
+}
+
+// NOTE:  Compiles the requires clauses, the body, and the ensures clauses of a lambda whose parameters are already
+// bound in c.locals.  Contracts are left out entirely when they are turned off.
+fn compile_lambda_body( c : &mut C, lambda : &Lambda, ret_type : Option<Type>, address_map : &M, natives : &Natives, symbols : &mut SymbolTable ) -> Result<Vec<I>, StaticError> {
+    let name = lambda.name.clone().unwrap_or_else(|| "lambda".into());
+    let mut prog = vec![];
+
+    if c.contracts {
+        for clause in &lambda.requires {
+            let (result, mut clause_prog) = compile_expr(c, clause, address_map, natives, symbols)?;
+            prog.append(&mut clause_prog);
+            prog.push(instr::panic_on_false(result, format!("{} requires {} failed", name, clause)));
+        }
+    }
+
+    // NOTE:  A body that uses ? is run as its own program so that the {error, E} tuple one of them returns early
+    // takes the place of the result.  The return annotation and ensures clauses still apply to it.
+    let (ret, mut body_prog) = if propagates(&lambda.body) {
+        let captured = captured_locals(c);
+        let cell = instr::Cell::default();
+
+        let mut inner = c.fresh();
+        let mut block_prog = load_from_cell(&mut inner, &cell, captured.iter().map(|(var, _)| var));
+        let (ret, mut prog) = compile_block(&mut inner, &lambda.body, &name, address_map, natives, symbols)?;
+        block_prog.append(&mut prog);
+        block_prog.push(Instr::Return(ret));
+        c.absorb(inner);
+
+        let result = c.symbol();
+        let captured_syms = captured.into_iter().map(|(_, sym)| sym).collect();
+        (result, vec![instr::catch_return(captured_syms, cell, block_prog, result)])
+    }
+    else {
+        compile_block(c, &lambda.body, &name, address_map, natives, symbols)?
+    };
+    prog.append(&mut body_prog);
+
+    if let Some(t) = ret_type {
+        prog.push(instr::check_type(ret, t));
+    }
+
+    if c.contracts {
+        for (var, clause) in &lambda.ensures {
+            // NOTE:  The ensures variable can shadow a parameter for the length of the clause.
+            let shadowed = c.locals.insert(var.clone(), ret);
+            let compiled = compile_expr(c, clause, address_map, natives, symbols);
+            match shadowed {
+                Some(sym) => { c.locals.insert(var.clone(), sym); },
+                None => { c.locals.remove(var); },
+            }
+            let (result, mut clause_prog) = compiled?;
+            prog.append(&mut clause_prog);
+            prog.push(instr::panic_on_false(result, format!("{} ensures {} -> {} failed", name, var, clause)));
+        }
+    }
+
+    prog.push(Instr::Return(ret));
+    Ok(prog)
+}
+
+pub fn check_pattern( pattern : &Pat ) -> Result<(), StaticError> {
+    for p in pattern.to_lax() {
+        match p {
+            Pat::Or(ps) => or_alternatives_bind_same_variables(ps)?,
+            Pat::Range(Some(start), Some(RangeEnd::Inclusive(end))) if start > end => 
+                return Err(StaticError::EmptyRangePattern { start: *start, end: *end, inclusive: true }),
+            Pat::Range(Some(start), Some(RangeEnd::Exclusive(end))) if start >= end => 
+                return Err(StaticError::EmptyRangePattern { start: *start, end: *end, inclusive: false }),
+            Pat::Map(kvps) if kvps.iter().any(|(key, _)| !is_constant(key)) => 
+                return Err(StaticError::MapPatternKeyNotConstant),
+            // NOTE:  With a variable (or any other non literal) on both sides there is no single way to split the string.
+            Pat::StringConcat(a, b) if !matches!(**a, Pat::String(_)) && !matches!(**b, Pat::String(_)) =>
+                return Err(StaticError::StringConcatPatternWithoutLiteral),
+            _ => { },
+        }
+    }
+    Ok(())
+}
+
+fn is_constant( lit : &Lit ) -> bool {
+    match lit {
+        Lit::Number(_) | Lit::Int(_) | Lit::BigInt(_) | Lit::Rational(_) | Lit::String(_) | Lit::Char(_) | Lit::Symbol(_) => true,
+        Lit::List(xs) | Lit::Tuple(xs) => xs.iter().all(is_constant),
+        Lit::Variable(_) | Lit::Map(_) | Lit::Lambda(_) => false,
+    }
+}
+
+fn or_alternatives_bind_same_variables( alternatives : &Vec<Pat> ) -> Result<(), StaticError> {
+    fn vars(p : &Pat) -> Vec<String> {
+        let mut ret = p.variables_to_bind().map(|x| x.to_string()).collect::<Vec<_>>();
+        ret.sort();
+        ret
+    }
+
+    let mut alternatives = alternatives.iter();
+
+    let expected = match alternatives.next() {
+        Some(p) => vars(p),
+        None => { return Ok(()); },
+    };
+
+    for alternative in alternatives {
+        let observed = vars(alternative);
+        if observed != expected {
+            return Err(StaticError::OrPatternBindsDifferentVariables { expected, observed });
+        }
+    }
+
+    Ok(())
 }
 
 fn lambda_variables_are_unique( lambda : &Lambda ) -> Result<(), StaticError> {
@@ -186,4 +556,145 @@ fn lambda_variables_are_unique( lambda : &Lambda ) -> Result<(), StaticError> {
 
 #[cfg(test)] 
 mod test {
-}
\ No newline at end of file
+    use super::*;
+    use super::super::trace;
+
+    fn pattern(input : &str) -> Pat {
+        let tokens = crate::parsing::tokenizer::tokenize(&format!("let {} = 0;", input)).unwrap();
+        let mut top = crate::parsing::parser::parse(&tokens).unwrap();
+        top.lets.remove(0).pattern
+    }
+
+    #[test]
+    fn check_pattern_should_allow_or_alternatives_with_same_variables() {
+        let p = pattern("({ X, Y } | [Y, X] | Y @ { X })");
+
+        assert!( check_pattern(&p).is_ok() );
+    }
+
+    #[test]
+    fn check_pattern_should_reject_or_alternatives_with_different_variables() {
+        let p = pattern("[ _, ({ X } | [Y]) ]");
+
+        assert!( matches!( check_pattern(&p), Err(StaticError::OrPatternBindsDifferentVariables { .. }) ) );
+    }
+
+    #[test]
+    fn check_pattern_should_reject_empty_ranges() {
+        assert!( matches!( check_pattern(&pattern("5..5")), Err(StaticError::EmptyRangePattern { .. }) ) );
+        assert!( matches!( check_pattern(&pattern("6..=5")), Err(StaticError::EmptyRangePattern { .. }) ) );
+        assert!( check_pattern(&pattern("5..=5")).is_ok() );
+    }
+
+    #[test]
+    fn check_pattern_should_reject_string_concat_without_literal() {
+        assert!( matches!( check_pattern(&pattern("A ++ B")), Err(StaticError::StringConcatPatternWithoutLiteral) ) );
+        assert!( matches!( check_pattern(&pattern(r#"A ++ "-" ++ B"#)), Err(StaticError::StringConcatPatternWithoutLiteral) ) );
+        assert!( check_pattern(&pattern(r#""GET " ++ Path ++ ".json""#)).is_ok() );
+    }
+
+    #[test]
+    fn check_pattern_should_reject_non_constant_map_keys() {
+        assert!( matches!( check_pattern(&pattern("%{ X => Y }")), Err(StaticError::MapPatternKeyNotConstant) ) );
+        assert!( check_pattern(&pattern("%{ {a, 1} => Y }")).is_ok() );
+    }
+
+    // NOTE:  Compiles the lambda that input evaluates to and calls it with params.
+    fn try_call_lambda(input : &str, contracts : bool, params : Vec<RuntimeData>) -> Result<RuntimeData, Box<dyn std::error::Error>> {
+        let tokens = crate::parsing::tokenizer::tokenize(input).unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+        let natives = Natives::new();
+        let mut heap = Heap::new();
+        let program = compile(&top.expr.unwrap(), &HashMap::new(), &natives, heap.symbols_mut(), contracts, &SourceMap::new(None, input))?;
+        let func = match purple::run(&HashMap::from([(Func(0), program)]), &mut heap)?.unwrap() {
+            Data::Func(f) | Data::Value(RuntimeData::Function(f)) => f,
+            Data::Value(v) => panic!("expected a lambda but found {:?}", v),
+        };
+        let data = natives.lambdas().call(&mut heap, func, params)?;
+        Ok(crate::natives::deref(&heap, &data)?.clone())
+    }
+
+    fn call_lambda(input : &str, params : Vec<RuntimeData>) -> RuntimeData {
+        try_call_lambda(input, true, params).unwrap()
+    }
+
+    #[test]
+    fn compile_should_call_lambda_with_params_in_order() {
+        let output = call_lambda("fun(X, Y) { {X, Y} }", vec![RuntimeData::Int(1), RuntimeData::Int(2)]);
+
+        assert!( matches!( output, RuntimeData::Tuple(ref items) if matches!( items[..], [RuntimeData::Int(1), RuntimeData::Int(2)] ) ) );
+    }
+
+    #[test]
+    fn compile_should_strip_contracts_when_turned_off() {
+        let input = "fun(X) requires is_empty(X) { length(X) }";
+
+        let with = try_call_lambda(input, true, vec![RuntimeData::List(vec![RuntimeData::Int(1)])]);
+        let without = try_call_lambda(input, false, vec![RuntimeData::List(vec![RuntimeData::Int(1)])]);
+
+        assert!( matches!( trace::root(&*with.unwrap_err()).downcast_ref::<DynamicError>(), Some(DynamicError::Panic(_)) ) );
+        assert!( matches!( without.unwrap(), RuntimeData::Int(1) ) );
+    }
+
+    #[test]
+    fn compile_should_keep_params_shadowed_by_ensures() {
+        let output = call_lambda("fun(R) ensures R -> R == 2 ensures Y -> R == 1 { 2 }", vec![RuntimeData::Int(1)]);
+
+        assert!( matches!( output, RuntimeData::Int(2) ) );
+    }
+
+    #[test]
+    fn compile_should_resolve_lambda_locals() {
+        let tokens = crate::parsing::tokenizer::tokenize("fun(X) { let Y = X; Z }").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = compile(&top.expr.unwrap(), &HashMap::new(), &Natives::new(), &mut SymbolTable::new(), true, &SourceMap::default());
+
+        assert!( matches!( output, Err(StaticError::VariableNotDefined(x)) if x == "Z" ) );
+    }
+
+    #[test]
+    fn compile_should_unwrap_ok_with_propagate() {
+        let output = call_lambda("fun() { let X = {ok, 1}?; X }", vec![]);
+
+        assert!( matches!( output, RuntimeData::Int(1) ) );
+    }
+
+    #[test]
+    fn compile_should_return_error_early_with_propagate() {
+        let output = call_lambda("fun() { let X = {error, 2}?; let Y = {ok, 3}?; {ok, X} }", vec![]);
+
+        assert!( matches!( output, RuntimeData::Tuple(ref items) if matches!( items[1], RuntimeData::Int(2) ) ) );
+    }
+
+    #[test]
+    fn compile_should_reject_propagate_outside_lambda() {
+        let tokens = crate::parsing::tokenizer::tokenize("{ok, 1}?").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = compile(&top.expr.unwrap(), &HashMap::new(), &Natives::new(), &mut SymbolTable::new(), true, &SourceMap::default());
+
+        assert!( matches!( output, Err(StaticError::PropagateOutsideLambda(_)) ) );
+    }
+
+    #[test]
+    fn compile_should_add_lambda_frame_on_entry() {
+        let output = try_call_lambda("\n\nfun(X) requires X == 1 {\n    X\n}", true, vec![RuntimeData::Int(2)]);
+
+        let error = output.unwrap_err();
+        let traceback = error.downcast_ref::<trace::Traceback>().unwrap();
+        assert_eq!( traceback.frames.len(), 1 );
+        assert_eq!( traceback.frames[0].to_string(), "fun (input:3)" );
+    }
+
+    #[test]
+    fn compile_should_give_lambda_frame_the_line_of_the_failed_call() {
+        let output = try_call_lambda("fun() {\n    let X = {ok, oops}?;\n    raise(X)\n}", true, vec![]);
+
+        let error = output.unwrap_err();
+        let traceback = error.downcast_ref::<trace::Traceback>().unwrap();
+        assert_eq!( traceback.frames.len(), 1 );
+        assert_eq!( traceback.frames[0].line, 3 );
+        assert!( matches!( traceback.error.downcast_ref::<DynamicError>(), Some(DynamicError::Uncaught(RuntimeData::Symbol(_), _)) ) );
+    }
+}
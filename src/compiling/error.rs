@@ -1,10 +1,24 @@
 
 use purple::data::*;
+use crate::runtime::RuntimeData;
+use crate::ast::Bound;
 
 #[derive(Debug)]
 pub enum StaticError {
     DuplicateVariableDefinitions(String),
     VariableNotDefined(String),
+    OrPatternBindsDifferentVariables { expected : Vec<String>, observed : Vec<String> },
+    EmptyRangePattern { start : Bound, end : Bound, inclusive : bool },
+    StringConcatPatternWithoutLiteral,
+    FunctionNotDefined(String),
+    MapPatternKeyNotConstant,
+    CannotReadModule { path : String, reason : String },
+    CannotParseModule { path : String, reason : String },
+    CyclicImport(Vec<String>),
+    DuplicateImport(String),
+    ExportNotDefined(String),
+    InvalidTypeAnnotation(String),
+    PropagateOutsideLambda(String),
     Todo
 }
 
@@ -13,6 +27,20 @@ impl std::fmt::Display for StaticError {
         match self {
             StaticError::DuplicateVariableDefinitions(s) => write!(f, "encountered duplicate variable definitions: {}", s),
             StaticError::VariableNotDefined(s) => write!(f, "encountered undefined variable: {}", s),
+            StaticError::OrPatternBindsDifferentVariables { expected, observed } => 
+                write!(f, "or pattern alternatives bind different variables expected [{}], but found [{}]", expected.join(", "), observed.join(", ")),
+            StaticError::EmptyRangePattern { start, end, inclusive : true } => write!(f, "range pattern can never match: {}..={}", start, end),
+            StaticError::EmptyRangePattern { start, end, inclusive : false } => write!(f, "range pattern can never match: {}..{}", start, end),
+            StaticError::StringConcatPatternWithoutLiteral => write!(f, "string concat pattern requires a string literal on one side"),
+            StaticError::FunctionNotDefined(s) => write!(f, "encountered undefined function: {}", s),
+            StaticError::MapPatternKeyNotConstant => write!(f, "map pattern keys must be constant literals"),
+            StaticError::CannotReadModule { path, reason } => write!(f, "cannot read module {}: {}", path, reason),
+            StaticError::CannotParseModule { path, reason } => write!(f, "cannot parse module {}: {}", path, reason),
+            StaticError::CyclicImport(paths) => write!(f, "encountered cyclic import: {}", paths.join(" -> ")),
+            StaticError::DuplicateImport(s) => write!(f, "encountered duplicate import name: {}", s),
+            StaticError::ExportNotDefined(s) => write!(f, "exported variable is not defined: {}", s),
+            StaticError::InvalidTypeAnnotation(s) => write!(f, "invalid type annotation: {}", s),
+            StaticError::PropagateOutsideLambda(s) => write!(f, "? can only be used inside of a lambda: {}", s),
             StaticError::Todo => write!(f, "TODO"),
         }
     }
@@ -26,6 +54,29 @@ pub enum DynamicError {
     Panic(String),
     TypeMismatch { expected : String, observed : String },
     CannotFindHeapAddress,
+    FunctionNotDefined(String),
+    WrongArity { name : String, expected : usize, observed : usize },
+    NotCallable(String),
+    InvalidIndex(f64),
+    IndexOutOfRange { index : usize, length : usize },
+    CannotParseNumber(String),
+    UnknownOption { name : String, option : String },
+    OptionOutOfRange { name : String, option : String, value : usize, max : usize },
+    IntegerOverflow(String),
+    DivisionByZero,
+    CannotConvertToInt(f64),
+    CannotConvertToRational(f64),
+    InvalidCodePoint(i64),
+    InvalidRange { start : String, end : String },
+    // NOTE:  A value passed to raise that no catch clause matched along with how it is shown (see natives::show), since
+    // showing it needs the heap.
+    Uncaught(RuntimeData, String),
+    // NOTE:  The {error, E} tuple that a ? is returning from the enclosing lambda (see instr::catch_return) along with
+    // how it is shown.
+    Return(RuntimeData, String),
+    // NOTE:  An error that isn't a DynamicError (like a traceback) from a lambda that a native called.  instr::call
+    // unwraps it again.
+    Nested(Box<dyn std::error::Error>),
 }
 
 impl std::fmt::Display for DynamicError {
@@ -34,6 +85,23 @@ impl std::fmt::Display for DynamicError {
             DynamicError::Panic(s) => write!(f, "panic: {}", s),
             DynamicError::TypeMismatch { expected, observed } => write!(f, "type mismatch expected {}, but found {}", expected, observed),
             DynamicError::CannotFindHeapAddress => write!(f, "cannot find heap address"),
+            DynamicError::FunctionNotDefined(s) => write!(f, "encountered undefined function: {}", s),
+            DynamicError::WrongArity { name, expected, observed } => write!(f, "{} expected {} parameters, but found {}", name, expected, observed),
+            DynamicError::NotCallable(s) => write!(f, "cannot call {}", s),
+            DynamicError::InvalidIndex(x) => write!(f, "index must be a non-negative integer, but found {}", x),
+            DynamicError::IndexOutOfRange { index, length } => write!(f, "index {} is out of range for length {}", index, length),
+            DynamicError::CannotParseNumber(s) => write!(f, "cannot parse number from string: {:?}", s),
+            DynamicError::UnknownOption { name, option } => write!(f, "{} does not support the option {}", name, option),
+            DynamicError::OptionOutOfRange { name, option, value, max } => write!(f, "{} option {} must be at most {}, but found {}", name, option, max, value),
+            DynamicError::IntegerOverflow(s) => write!(f, "integer overflow in {}", s),
+            DynamicError::DivisionByZero => write!(f, "exact division by zero"),
+            DynamicError::CannotConvertToInt(x) => write!(f, "cannot convert {} to an integer", x),
+            DynamicError::CannotConvertToRational(x) => write!(f, "cannot convert {} to a rational", x),
+            DynamicError::InvalidCodePoint(x) => write!(f, "{} is not a unicode scalar value", x),
+            DynamicError::InvalidRange { start, end } => write!(f, "range from {} to {} is too large or not finite", start, end),
+            DynamicError::Uncaught(_, shown) => write!(f, "uncaught raise: {}", shown),
+            DynamicError::Return(_, shown) => write!(f, "? returned outside of a lambda: {}", shown),
+            DynamicError::Nested(e) => write!(f, "{}", e),
         }
     }
 }
@@ -1,29 +1,59 @@
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use purple::data::*;
 
-use crate::ast::*;
+use crate::evaling::pattern_matcher::InternedPat;
 use crate::runtime::*;
+use crate::natives::Natives;
+use crate::typing::types::Type;
+use crate::typing::annotation::conforms;
 
 use super::error::*;
+use super::trace;
 
 pub fn panic_on_false(target : Symbol, message : String) -> Instr<RuntimeData, Heap> {
 
     Instr::<RuntimeData, Heap>::SysCall(Box::new(
         move |locals, heap| {
             match locals.get(&target)? {
-                Data::Value(RuntimeData::Symbol(v)) if v == "true" => Ok(()),
+                Data::Value(RuntimeData::Symbol(SymbolTable::TRUE)) => Ok(()),
                 _ => Err(Box::new(DynamicError::Panic(message.clone()))),
             }
         }))
 }
 
-pub fn pattern_match(data : Symbol, pattern : Pat, result : Symbol, var_to_sym : HashMap<String, Symbol>) -> Instr<RuntimeData, Heap> {
+// NOTE:  Checks a value against a type annotation.
+pub fn check_type(data : Symbol, t : Type) -> Instr<RuntimeData, Heap> {
+    Instr::<RuntimeData, Heap>::SysCall(Box::new(
+        move |locals, heap| {
+            let data = match locals.get(&data)? {
+                Data::Func(f) => RuntimeData::Function(f),
+                Data::Value(v) => v,
+            };
+            match conforms(&t, &data, heap) {
+                Ok(()) => Ok(()),
+                Err(err) => Err(Box::new(err)),
+            }
+        }))
+}
+
+pub fn pattern_match(data : Symbol, pattern : InternedPat, result : Symbol, var_to_sym : HashMap<String, Symbol>, pin_to_sym : HashMap<String, Symbol>) -> Instr<RuntimeData, Heap> {
     use crate::evaling::pattern_matcher::*; 
 
     Instr::<RuntimeData, Heap>::LoadFromSysCall(result, Box::new(
         move |locals, heap| {
+            let mut pins = HashMap::new();
+            for (name, sym) in pin_to_sym.iter() {
+                let value = match locals.get(sym)? {
+                    Data::Func(f) => RuntimeData::Function(f),
+                    Data::Value(v) => v,
+                };
+                pins.insert(name.clone(), value);
+            }
+
             let data = {
                 match locals.get(&data)? {
                     Data::Func(f) => RuntimeData::Function(f),
@@ -33,17 +63,183 @@ pub fn pattern_match(data : Symbol, pattern : Pat, result : Symbol, var_to_sym :
                 }
             };
 
-            match pattern_match(&pattern, &data) {
+            match pattern_match(&pattern, &data, heap, &pins) {
                 MatchResult::Fatal(err) => { return Err(Box::new(err))},
-                MatchResult::NoMatch => Ok(Data::Value(RuntimeData::Symbol("false".into()))),
+                MatchResult::NoMatch => Ok(Data::Value(RuntimeData::Symbol(SymbolTable::FALSE))),
                 MatchResult::Env(bounds) => {
                     for BoundData { name, data } in bounds {
                         let address = heap.insert_new(data);
                         let sym = var_to_sym.get(&name).expect("var_to_sym missing variable");
                         locals.set(sym, Data::Value(RuntimeData::Address(address)))?;
                     }
-                    Ok(Data::Value(RuntimeData::Symbol("true".into())))
+                    Ok(Data::Value(RuntimeData::Symbol(SymbolTable::TRUE)))
+                },
+            }
+        }))
+}
+
+// NOTE:  Passes values into a program that is run by another instruction (see try_catch).
+pub type Cell = Rc<RefCell<Vec<RuntimeData>>>;
+
+pub fn load_from_cell(cell : Cell, index : usize, sym : Symbol) -> Instr<RuntimeData, Heap> {
+    Instr::<RuntimeData, Heap>::LoadFromSysCall(sym, Box::new(
+        move |_locals, _heap| {
+            match cell.borrow().get(index) {
+                Some(RuntimeData::Function(f)) => Ok(Data::Func(*f)),
+                Some(data) => Ok(Data::Value(data.clone())),
+                None => Err(Box::new(DynamicError::CannotFindHeapAddress)),
+            }
+        }))
+}
+
+pub fn run_with_cell(prog : &HashMap<Func, Vec<Instr<RuntimeData, Heap>>>, cell : &Cell, values : Vec<RuntimeData>, heap : &mut Heap) -> Result<Data<RuntimeData>, Box<dyn std::error::Error>> {
+    // NOTE:  The previous contents are restored in case the same program is run again while it is running.
+    let saved = cell.replace(values);
+    let result = purple::run(prog, heap);
+    cell.replace(saved);
+    Ok(result?.unwrap_or(Data::Value(RuntimeData::Tuple(vec![]))))
+}
+
+// NOTE:  A catch clause.  The program expects the captured locals followed by the values bound to vars in its cell.
+pub struct Handler {
+    pub pattern : InternedPat,
+    pub vars : Vec<String>,
+    pub pins : HashMap<String, Symbol>,
+    pub prog : Vec<Instr<RuntimeData, Heap>>,
+}
+
+// NOTE:  Runs block with the captured locals.  A value raised inside of it is matched against the handlers in order
+// and the first one that matches is run instead.  When none of them match the value is raised again.
+pub fn try_catch(captured : Vec<Symbol>, cell : Cell, block : Vec<Instr<RuntimeData, Heap>>, handlers : Vec<Handler>, result : Symbol) -> Instr<RuntimeData, Heap> {
+    use crate::evaling::pattern_matcher::*; 
+
+    fn value(data : Data<RuntimeData>) -> RuntimeData {
+        match data {
+            Data::Func(f) => RuntimeData::Function(f),
+            Data::Value(v) => v,
+        }
+    }
+
+    let block = HashMap::from([(Func(0), block)]);
+    let handlers = handlers.into_iter().map(|h| (h.pattern, h.vars, h.pins, HashMap::from([(Func(0), h.prog)]))).collect::<Vec<_>>();
+
+    Instr::<RuntimeData, Heap>::LoadFromSysCall(result, Box::new(
+        move |locals, heap| {
+            let mut values = vec![];
+            for sym in captured.iter() {
+                values.push(value(locals.get(sym)?));
+            }
+
+            // NOTE:  The raise may have passed through lambdas that added frames on the way out.
+            let (raised, shown) = match run_with_cell(&block, &cell, values.clone(), heap) {
+                Ok(data) => { return Ok(data); },
+                Err(err) => match trace::root(&*err).downcast_ref::<DynamicError>() {
+                    Some(DynamicError::Uncaught(raised, shown)) => (raised.clone(), shown.clone()),
+                    _ => { return Err(err); },
+                },
+            };
+
+            for (pattern, vars, pin_to_sym, prog) in handlers.iter() {
+                let mut pins = HashMap::new();
+                for (name, sym) in pin_to_sym.iter() {
+                    pins.insert(name.clone(), value(locals.get(sym)?));
+                }
+
+                match pattern_match(pattern, &raised, heap, &pins) {
+                    MatchResult::Fatal(err) => { return Err(Box::new(err)); },
+                    MatchResult::NoMatch => { },
+                    MatchResult::Env(bounds) => {
+                        let mut bound = bounds.into_iter().map(|BoundData { name, data }| (name, data)).collect::<HashMap<_, _>>();
+                        let mut values = values.clone();
+                        for var in vars.iter() {
+                            let data = bound.remove(var).expect("handler missing bound variable");
+                            values.push(RuntimeData::Address(heap.insert_new(data)));
+                        }
+                        return run_with_cell(prog, &cell, values, heap);
+                    },
+                }
+            }
+
+            Err(Box::new(DynamicError::Uncaught(raised, shown)))
+        }))
+}
+
+// NOTE:  Runs the body of a lambda that uses ? so that the {error, E} tuple returned early by one of them becomes
+// the result.
+pub fn catch_return(captured : Vec<Symbol>, cell : Cell, body : Vec<Instr<RuntimeData, Heap>>, result : Symbol) -> Instr<RuntimeData, Heap> {
+    let body = HashMap::from([(Func(0), body)]);
+
+    Instr::<RuntimeData, Heap>::LoadFromSysCall(result, Box::new(
+        move |locals, heap| {
+            let mut values = vec![];
+            for sym in captured.iter() {
+                values.push(match locals.get(sym)? {
+                    Data::Func(f) => RuntimeData::Function(f),
+                    Data::Value(v) => v,
+                });
+            }
+
+            match run_with_cell(&body, &cell, values, heap) {
+                Ok(data) => Ok(data),
+                Err(err) => match trace::root(&*err).downcast_ref::<DynamicError>() {
+                    Some(DynamicError::Return(data, _)) => Ok(Data::Value(data.clone())),
+                    _ => Err(err),
+                },
+            }
+        }))
+}
+
+// NOTE:  Unwraps {ok, V} to V.  An {error, E} tuple is returned from the enclosing lambda as is.
+pub fn propagate(data : Symbol, result : Symbol) -> Instr<RuntimeData, Heap> {
+    use crate::natives::{deref, mismatch, show};
+
+    Instr::<RuntimeData, Heap>::LoadFromSysCall(result, Box::new(
+        move |locals, heap| {
+            let data = match locals.get(&data)? {
+                Data::Func(f) => RuntimeData::Function(f),
+                Data::Value(v) => v,
+            };
+            let tuple = deref(heap, &data)?;
+            let tag = match tuple {
+                RuntimeData::Tuple(items) if items.len() == 2 => match deref(heap, &items[0])? {
+                    RuntimeData::Symbol(id) => heap.symbols().name(*id),
+                    _ => "",
+                },
+                _ => "",
+            };
+            match (tag, tuple) {
+                ("ok", RuntimeData::Tuple(items)) => match &items[1] {
+                    RuntimeData::Function(f) => Ok(Data::Func(*f)),
+                    v => Ok(Data::Value(v.clone())),
                 },
+                ("error", tuple) => Err(Box::new(DynamicError::Return(tuple.clone(), show(heap, tuple, true)?))),
+                (_, tuple) => Err(Box::new(mismatch("{ok, V} or {error, E}", tuple))),
+            }
+        }))
+}
+
+// NOTE:  line is where the call is in the source, for the frame that a failure leaves through (see trace::at_call).
+pub fn call(func : Symbol, params : Vec<Symbol>, result : Symbol, natives : Natives, line : usize) -> Instr<RuntimeData, Heap> {
+    Instr::<RuntimeData, Heap>::LoadFromSysCall(result, Box::new(
+        move |locals, heap| {
+            let mut ps = vec![];
+            for param in params.iter() {
+                match locals.get(param)? {
+                    Data::Func(f) => ps.push(RuntimeData::Function(f)),
+                    Data::Value(v) => ps.push(v),
+                }
+            }
+
+            let callee = match locals.get(&func)? {
+                Data::Func(f) => RuntimeData::Function(f),
+                Data::Value(v) => v,
+            };
+
+            match natives.apply(heap, &callee, ps) {
+                Ok(RuntimeData::Function(f)) => Ok(Data::Func(f)),
+                Ok(data) => Ok(Data::Value(data)),
+                Err(DynamicError::Nested(err)) => Err(trace::at_call(err, line)),
+                Err(err) => Err(trace::at_call(Box::new(err), line)),
             }
         }))
 }
@@ -108,6 +304,43 @@ pub fn push_into_tuple_in_heap(item : Symbol, tuple_address : Symbol) -> Instr::
     ))
 }
 
+pub fn put_into_map_in_heap(key : Symbol, value : Symbol, map_address : Symbol) -> Instr::<RuntimeData, Heap> { 
+
+    fn error(expected : &str, observed : &str) -> Result<(), Box<dyn std::error::Error>> {
+        Err(Box::new(DynamicError::TypeMismatch { expected: expected.into(), observed: observed.into()}))
+    }
+
+    Instr::<RuntimeData, Heap>::SysCall(Box::new(
+        move |locals, heap| {
+            let key = match locals.get(&key)? {
+                Data::Value(x) => x,
+                _ => { return error("Data::Value", "Data::Func"); }
+            };
+
+            let value = match locals.get(&value)? {
+                Data::Value(x) => x,
+                _ => { return error("Data::Value", "Data::Func"); }
+            };
+
+            let map_address = match locals.get(&map_address)? {
+                Data::Value(RuntimeData::Address(x)) => x,
+                Data::Value(_) => { return error("Data::Value(RuntimeData::Address", "Data::Value(?)"); },
+                Data::Func(_) => { return error("Data::Value(RuntimeData::Address)", "Data::Func"); },
+            };
+
+            // NOTE:  The entries are taken out of the heap so that the keys can be compared against the heap.
+            let mut entries = match heap.get_mut(map_address).ok_or(Box::new(DynamicError::CannotFindHeapAddress))? {
+                RuntimeData::Map(m) => std::mem::take(m),
+                _ => { return error( "RuntimeData::Map", "?" ); },
+            };
+
+            map_insert(heap, &mut entries, key, value).ok_or(Box::new(DynamicError::CannotFindHeapAddress))?;
+            heap.insert(map_address, RuntimeData::Map(entries));
+            Ok(())
+        }
+    ))
+}
+
 pub fn push_into_list_in_heap(item : Symbol, list_address : Symbol) -> Instr::<RuntimeData, Heap> { 
 
     fn error(expected : &str, observed : &str) -> Result<(), Box<dyn std::error::Error>> {
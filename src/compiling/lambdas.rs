@@ -0,0 +1,58 @@
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use purple::data::*;
+
+use crate::runtime::*;
+
+use super::error::DynamicError;
+use super::instr::{self, Cell};
+use super::trace::{self, Frame};
+
+// NOTE:  A compiled lambda.  The body is its own program that starts by loading its parameters from cell, so that
+// it can be run by whatever calls it (see instr::call and Natives::apply).  frame is added to any error that leaves
+// the body.
+pub struct Compiled {
+    pub name : String,
+    pub arity : usize,
+    pub cell : Cell,
+    pub prog : HashMap<Func, Vec<Instr<RuntimeData, Heap>>>,
+    pub frame : Frame,
+}
+
+// NOTE:  Every lambda compiled so far by Func.  Clones share the same table, so a lambda compiled by one eval can be
+// called by any later one.
+#[derive(Clone, Default)]
+pub struct Lambdas {
+    table : Rc<RefCell<HashMap<Func, Rc<Compiled>>>>,
+}
+
+impl Lambdas {
+    // NOTE:  The highest Func compiled so far.  Func(0) is left for the program being evaluated.
+    pub fn last_func(&self) -> usize {
+        self.table.borrow().keys().map(|f| f.0).max().unwrap_or(0)
+    }
+
+    pub fn insert(&self, func : Func, lambda : Compiled) {
+        self.table.borrow_mut().insert(func, Rc::new(lambda));
+    }
+
+    pub fn call(&self, heap : &mut Heap, func : Func, params : Vec<RuntimeData>) -> Result<RuntimeData, Box<dyn std::error::Error>> {
+        // NOTE:  The table isn't borrowed while the body runs because the body can call other lambdas.
+        let lambda = match self.table.borrow().get(&func) {
+            Some(lambda) => lambda.clone(),
+            None => { return Err(Box::new(DynamicError::FunctionNotDefined(format!("{:?}", func)))); },
+        };
+
+        if params.len() != lambda.arity {
+            return Err(Box::new(DynamicError::WrongArity { name: lambda.name.clone(), expected: lambda.arity, observed: params.len() }));
+        }
+
+        match instr::run_with_cell(&lambda.prog, &lambda.cell, params, heap).map_err(|e| trace::push_frame(e, &lambda.frame))? {
+            Data::Func(f) => Ok(RuntimeData::Function(f)),
+            Data::Value(v) => Ok(v),
+        }
+    }
+}
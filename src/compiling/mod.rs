@@ -2,3 +2,5 @@
 mod instr;
 pub mod error;
 pub mod compiler;
+pub mod trace;
+pub mod lambdas;
@@ -0,0 +1,179 @@
+
+use std::path::PathBuf;
+
+use super::error::DynamicError;
+
+// NOTE:  A lambda or a top level let along with where it is in the source.  Once an error has passed through it, line
+// is the line of the call that the error left through when there was one.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name : String,
+    pub file : Option<PathBuf>,
+    pub line : usize,
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{} ({}:{})", self.name, file.display(), self.line),
+            None => write!(f, "{} (input:{})", self.name, self.line),
+        }
+    }
+}
+
+// NOTE:  Turns the byte offsets kept in the ast into lines of the source being evaluated.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    file : Option<PathBuf>,
+    line_starts : Vec<usize>,
+}
+
+impl SourceMap {
+    pub fn new(file : Option<PathBuf>, source : &str) -> Self {
+        let line_starts = std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        SourceMap { file, line_starts }
+    }
+
+    pub fn frame(&self, name : String, position : usize) -> Frame {
+        Frame { name, file: self.file.clone(), line: self.line(position) }
+    }
+
+    pub fn locate(&self, error : Box<dyn std::error::Error>, position : usize) -> Located {
+        let line = self.line(position);
+        let column = position - self.line_starts.get(line - 1).copied().unwrap_or(0) + 1;
+        Located { error, file: self.file.clone(), line, column }
+    }
+
+    pub fn line(&self, position : usize) -> usize {
+        let line = match self.line_starts.binary_search(&position) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        };
+        line.max(1)
+    }
+}
+
+// NOTE:  An error that was found before anything ran (like by the type checker) along with where it is in the source.
+#[derive(Debug)]
+pub struct Located {
+    pub error : Box<dyn std::error::Error>,
+    pub file : Option<PathBuf>,
+    pub line : usize,
+    pub column : usize,
+}
+
+impl std::fmt::Display for Located {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}: {}", file.display(), self.line, self.column, self.error),
+            None => write!(f, "input:{}:{}: {}", self.line, self.column, self.error),
+        }
+    }
+}
+
+impl std::error::Error for Located {}
+
+// NOTE:  An error along with the frames it passed through, innermost first.  purple doesn't report which instruction
+// failed, so frames are added on the way out by Lambdas::call for every lambda and by the evaler for top level lets.
+// line is the line of the call that the error left the next frame through (see at_call).
+#[derive(Debug)]
+pub struct Traceback {
+    pub error : Box<dyn std::error::Error>,
+    pub frames : Vec<Frame>,
+    pub line : Option<usize>,
+}
+
+impl std::fmt::Display for Traceback {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.frames.is_empty() {
+            return write!(f, "{}", self.error);
+        }
+        writeln!(f, "traceback (most recent call last):")?;
+        for frame in self.frames.iter().rev() {
+            writeln!(f, "  in {}", frame)?;
+        }
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for Traceback {}
+
+fn traceback(error : Box<dyn std::error::Error>) -> Box<Traceback> {
+    match error.downcast::<Traceback>() {
+        Ok(traceback) => traceback,
+        Err(error) => Box::new(Traceback { error, frames: vec![], line: None }),
+    }
+}
+
+pub fn push_frame(error : Box<dyn std::error::Error>, frame : &Frame) -> Box<dyn std::error::Error> {
+    let mut traceback = traceback(error);
+    let mut frame = frame.clone();
+    if let Some(line) = traceback.line.take() {
+        frame.line = line;
+    }
+    traceback.frames.push(frame);
+    traceback
+}
+
+// NOTE:  Records the line of a call that failed.  The innermost call is kept when an error leaves through several
+// calls before the next frame (like through a try block).
+pub fn at_call(error : Box<dyn std::error::Error>, line : usize) -> Box<dyn std::error::Error> {
+    let mut traceback = traceback(error);
+    traceback.line.get_or_insert(line);
+    traceback
+}
+
+// NOTE:  The error without the frames it passed through, including errors that passed through a native.
+pub fn root<'a>(error : &'a (dyn std::error::Error + 'static)) -> &'a (dyn std::error::Error + 'static) {
+    if let Some(traceback) = error.downcast_ref::<Traceback>() {
+        return root(&*traceback.error);
+    }
+    match error.downcast_ref::<DynamicError>() {
+        Some(DynamicError::Nested(error)) => root(&**error),
+        _ => error,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_find_lines() {
+        let source = SourceMap::new(None, "let X = 1;\n\nlet Y = fun() {\n    X\n};");
+
+        assert_eq!( source.frame("let X".into(), 0).line, 1 );
+        assert_eq!( source.frame("let Y".into(), 12).line, 3 );
+        assert_eq!( source.frame("fun Y".into(), 20).line, 3 );
+        assert_eq!( source.frame("fun Y".into(), 32).line, 4 );
+    }
+
+    #[test]
+    fn should_locate_line_and_column() {
+        let source = SourceMap::new(Some("main.mint".into()), "let X = 1;\nlet Y = length(X);");
+
+        let output = source.locate(Box::new(DynamicError::DivisionByZero), 25);
+
+        assert_eq!( (output.line, output.column), (2, 15) );
+        assert_eq!( output.to_string(), "main.mint:2:15: exact division by zero" );
+    }
+
+    #[test]
+    fn should_render_outermost_frame_first() {
+        let source = SourceMap::new(Some("main.mint".into()), "let X = 1;\nlet F = fun() { 1 };");
+        let error = push_frame(Box::new(DynamicError::DivisionByZero), &source.frame("fun F".into(), 19));
+        let error = push_frame(error, &source.frame("let X".into(), 0));
+
+        assert_eq!( error.to_string(), "traceback (most recent call last):\n  in let X (main.mint:1)\n  in fun F (main.mint:2)\nexact division by zero" );
+        assert!( matches!( root(&*error).downcast_ref::<DynamicError>(), Some(DynamicError::DivisionByZero) ) );
+    }
+
+    #[test]
+    fn should_give_frame_the_line_of_the_failed_call() {
+        let source = SourceMap::new(None, "let F = fun() {\n    div(1r, 0)\n};");
+        let error = at_call(Box::new(DynamicError::DivisionByZero), 2);
+        let error = push_frame(error, &source.frame("fun F".into(), 8));
+
+        assert_eq!( error.to_string(), "traceback (most recent call last):\n  in fun F (input:2)\nexact division by zero" );
+    }
+}
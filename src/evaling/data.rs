@@ -1,19 +1,53 @@
 
-use std::collections::HashMap;
-
-use purple::data::*;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 use crate::runtime::*;
+use crate::natives::Natives;
+use crate::resolving::resolver::Resolver;
+use crate::typing::infer::Env;
+use crate::compiling::trace::SourceMap;
+
+use super::module::Module;
 
 
 pub struct Context {
     pub address_map : HashMap<String, HeapAddress>,
-    pub functions : HashMap<Func, Vec<Instr<RuntimeData, Heap>>>,
-    pub heap : Heap 
+    pub heap : Heap,
+    pub natives : Natives,
+    // NOTE:  Exported variables of every module loaded so far by canonical path.
+    pub modules : HashMap<PathBuf, Module>,
+    // NOTE:  Modules that are currently being loaded, outermost first, for cyclic import detection.
+    pub loading : Vec<PathBuf>,
+    // NOTE:  Names that modules have been imported as in the current namespace, including modules that export nothing.
+    pub imported : HashSet<String>,
+    // NOTE:  Imports are relative to the file being evaluated or to the working directory when there isn't one.
+    pub current_file : Option<PathBuf>,
+    pub resolver : Resolver,
+    // NOTE:  When check_types is set every input is type checked before it is compiled.  The types of the
+    // variables that have been checked so far are kept in types.
+    pub check_types : bool,
+    pub types : Env,
+    // NOTE:  Turning contracts off leaves requires and ensures clauses out of compiled lambdas.
+    pub contracts : bool,
+    // NOTE:  The source being evaluated for stack traces and error positions.
+    pub source : SourceMap,
 }
 
 impl Context {
     pub fn new() -> Self { 
-        Context { address_map: HashMap::new(), functions: HashMap::new(), heap: Heap::new() }
+        Context { address_map: HashMap::new()
+                , heap: Heap::new()
+                , natives: Natives::new()
+                , modules: HashMap::new()
+                , loading: vec![]
+                , imported: HashSet::new()
+                , current_file: None
+                , resolver: Resolver::new()
+                , check_types: false
+                , types: Env::new()
+                , contracts: true
+                , source: SourceMap::default()
+                }
     }
 }
@@ -6,15 +6,19 @@ pub enum RuntimeError {
     CannotSetBoundVariable(String),
     CannotPatternMatchAgainstLambda, // TODO:  This probably gets removed?
     PatternMatchFailed,
+    StringConcatPatternWithoutLiteral,
+    CannotFindHeapAddress,
 }
 
 impl std::fmt::Display for RuntimeError {
     fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             RuntimeError::VariableNotFound(s) => write!(f, "could not find variable:  {}", s),
-            RuntimeError::CannotSetBoundVariable(s) => write!(f, "cannot set already bound variable:  {}", s),
+            RuntimeError::CannotSetBoundVariable(s) => write!(f, "cannot set already bound variable:  {} (use ^{} to match against its value)", s, s),
             RuntimeError::CannotPatternMatchAgainstLambda => write!(f, "cannot pattern match against a lambda"),
             RuntimeError::PatternMatchFailed => write!(f, "pattern match failed"),
+            RuntimeError::StringConcatPatternWithoutLiteral => write!(f, "string concat pattern requires a string literal on one side"),
+            RuntimeError::CannotFindHeapAddress => write!(f, "cannot find heap address"),
         }
     }
 }
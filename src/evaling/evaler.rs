@@ -1,20 +1,54 @@
 
+use std::collections::HashMap;
+
 use purple::data::*;
 
 use crate::ast::*;
 use crate::runtime::*;
 use crate::compiling::compiler;
+use crate::compiling::error::StaticError;
+use crate::compiling::trace;
+use crate::typing::annotation;
+use crate::typing::types::Type;
 
 use super::data::Context;
 use super::error::RuntimeError;
 use super::pattern_matcher::*;
 
 pub fn eval( input : Top, context : &mut Context ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    for i in &input.imports {
+        super::module::import(i, context)?;
+    }
+
+    if context.check_types {
+        crate::typing::infer::check(&input, &mut context.types, &context.natives).map_err(|e| {
+            let position = e.position();
+            context.source.locate(Box::new(e), position)
+        })?;
+    }
+
     for l in input.lets {
-        let program = compiler::compile(&l.expr, &context.address_map, &mut context.functions)?;
-        // Note:  We can leave functions alone after we're done because the next eval will flush Func(0)
-        context.functions.insert(Func(0), program); 
-        let result = purple::run(&context.functions, &mut context.heap)?;
+        compiler::check_pattern(&l.pattern)?;
+        let ann = match &l.ann {
+            Some(ann) => {
+                let mut next_var = 0;
+                let t = annotation::to_type(ann, l.position, &mut HashMap::new(), &mut |_| { next_var += 1; Type::Var(next_var - 1) })
+                                  .map_err(|e| StaticError::InvalidTypeAnnotation(context.source.locate(Box::new(e), l.position).to_string()))?;
+                Some(t)
+            },
+            None => None,
+        };
+        let pins = l.pattern.variables_to_pin().map(|var| match context.address_map.get(var) {
+            Some(address) => Ok((var.to_string(), RuntimeData::Address(*address))),
+            None => Err(StaticError::VariableNotDefined(var.into())),
+        }).collect::<Result<HashMap<_, _>, _>>()?;
+        let program = compiler::compile(&l.expr, &context.address_map, &context.natives, context.heap.symbols_mut(), context.contracts, &context.source)?;
+        // NOTE:  Lambdas are run from context.natives when they are called, so only the program itself is needed.
+        let result = purple::run(&HashMap::from([(Func(0), program)]), &mut context.heap).map_err(|e| {
+            let vars = l.pattern.variables_to_bind().collect::<Vec<_>>();
+            let name = if vars.is_empty() { "let".to_string() } else { format!("let {}", vars.join(", ")) };
+            trace::push_frame(e.into(), &context.source.frame(name, l.position))
+        })?;
 
         if result.is_none() { 
             continue;
@@ -26,7 +60,13 @@ pub fn eval( input : Top, context : &mut Context ) -> Result<Option<String>, Box
             Data::Func(f) => RuntimeData::Function(f),
         };
 
-        match pattern_match(&l.pattern, &data) {
+        // NOTE:  Annotations are checked at runtime even when the type checker is off.
+        if let Some(t) = &ann {
+            annotation::conforms(t, &data, &context.heap)?;
+        }
+
+        let pattern = intern(&l.pattern, context.heap.symbols_mut())?;
+        match pattern_match(&pattern, &data, &context.heap, &pins) {
             MatchResult::Fatal(e) => { return Err(Box::new(e)); },
             MatchResult::NoMatch => { return Err(Box::new(RuntimeError::PatternMatchFailed)); },
             MatchResult::Env(bound) => { 
@@ -46,10 +86,8 @@ pub fn eval( input : Top, context : &mut Context ) -> Result<Option<String>, Box
         Ok(None)
     }
     else {
-        let program = compiler::compile(&input.expr.unwrap(), &context.address_map, &mut context.functions)?;
-        // Note:  We can leave functions alone after we're done because the next eval will flush Func(0)
-        context.functions.insert(Func(0), program); 
-        let result = purple::run(&context.functions, &mut context.heap)?;
+        let program = compiler::compile(&input.expr.unwrap(), &context.address_map, &context.natives, context.heap.symbols_mut(), context.contracts, &context.source)?;
+        let result = purple::run(&HashMap::from([(Func(0), program)]), &mut context.heap)?;
         match result {
             Some(Data::Value(v)) => Ok(Some(print_data(&v, &context.heap))),
             Some(Data::Func(f)) => Ok(Some(print_data(&RuntimeData::Function(f), &context.heap))),
@@ -67,10 +105,281 @@ fn print_data(data : &RuntimeData, heap : &Heap) -> String {
             format!("Address( {} )", print_data(deref, heap)) // TODO:  deal with looping derefs
         },
         Function(x) => format!("Function: {}", x.0),
-        Number(x) => format!("Number: {}", x),
+        Number(x) => format!("Number: {:?}", x),
+        Int(x) => format!("Int: {}", x),
+        Char(x) => format!("Char: {:?}", x),
+        // NOTE:  Exact numbers print with their literal suffix so that they read back in as the same value.
+        BigInt(x) => format!("BigInt: {}n", x),
+        Rational(x) => format!("Rational: {}r", x),
         String(x) => format!("String: {}", x),
-        Symbol(x) => format!("Symbol: {}", x),
+        Symbol(x) => format!("Symbol: {}", heap.symbols().name(*x)),
         List(x) => format!("List( {} )", x.iter().map(|d| print_data(d, heap)).collect::<Vec<_>>().join(", ")),
         Tuple(x) => format!("Tuple( {} )", x.iter().map(|d| print_data(d, heap)).collect::<Vec<_>>().join(", ")),
+        Map(x) => format!("Map( {} )", x.iter().map(|(k, v)| format!("{} => {}", print_data(k, heap), print_data(v, heap))).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compiling::error::DynamicError;
+
+    fn run(input : &str) -> Option<String> {
+        let mut context = Context::new();
+        let tokens = crate::parsing::tokenizer::tokenize(input).unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+        eval(top, &mut context).unwrap()
+    }
+
+    #[test]
+    fn should_eval_structural_equality() {
+        let output = run("[1, {2, a}] == [1, {2, a}]");
+
+        assert_eq!( output, Some("Symbol: true".into()) );
+    }
+
+    #[test]
+    fn should_eval_compare() {
+        let output = run("compare(1, \"a\")");
+
+        assert_eq!( output, Some("Symbol: lt".into()) );
+    }
+
+    #[test]
+    fn should_pin_global_variable() {
+        let output = run("let X = [1, 2]; let { ^X, Y } = { [1, 2], 3 }; Y");
+
+        assert_eq!( output, Some("Int: 3".into()) );
+    }
+
+    #[test]
+    fn should_eval_list_length() {
+        let output = run("length([1, 2, 3])");
+
+        assert_eq!( output, Some("Int: 3".into()) );
+    }
+
+    #[test]
+    fn should_eval_map_with_native() {
+        let output = run("map([[1], [2, 3]], length)");
+
+        assert_eq!( output, Some("List( Int: 1, Int: 2 )".into()) );
+    }
+
+    #[test]
+    fn should_eval_fold_left() {
+        let output = run("fold_left([[1], [2, 3]], [0], append)");
+
+        assert_eq!( output, Some("List( Int: 0, Int: 1, Int: 2, Int: 3 )".into()) );
+    }
+
+    #[test]
+    fn should_eval_sort_by() {
+        let output = run("reverse(sort_by(drop(range(0, 5), 2), compare))");
+
+        assert_eq!( output, Some("List( Int: 4, Int: 3, Int: 2 )".into()) );
+    }
+
+    #[test]
+    fn should_eval_zip_and_nth() {
+        let output = run("nth(zip([1, 2], [a, b, c]), 1)");
+
+        assert_eq!( output, Some("Tuple( Int: 2, Symbol: b )".into()) );
+    }
+
+    #[test]
+    fn should_eval_string_functions() {
+        let output = run("join(map(split(\"a b c\", \" \"), to_upper), \"-\")");
+
+        assert_eq!( output, Some("String: A-B-C".into()) );
+    }
+
+    #[test]
+    fn should_eval_symbol_string_conversions() {
+        let output = run("string_to_symbol(concat(symbol_to_string(ok), \"_then\")) == ok_then");
+
+        assert_eq!( output, Some("Symbol: true".into()) );
+    }
+
+    #[test]
+    fn should_eval_map_literal_and_pattern() {
+        let output = run("let %{ b => B } = put(%{ a => 1 }, b, 2); B");
+
+        assert_eq!( output, Some("Int: 2".into()) );
+    }
+
+    #[test]
+    fn should_eval_mixed_arithmetic() {
+        let output = run("add(div(7, 2), 0.5)");
+
+        assert_eq!( output, Some("Number: 3.5".into()) );
+    }
+
+    #[test]
+    fn should_eval_exact_arithmetic() {
+        let output = run("let 3/10r = add(0.1r, 0.2r); div(1r, 3)");
+
+        assert_eq!( output, Some("Rational: 1/3r".into()) );
+    }
+
+    #[test]
+    fn should_eval_char_pattern() {
+        let output = run("let ['h', C | _] = chars(\"hi\"); C");
+
+        assert_eq!( output, Some("Char: 'i'".into()) );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_eval_interpolation() {
+        let output = run("let Name = \"Ada\"; let Xs = [1, 2]; \"Hello ${Name}, you have ${length(Xs)} items: ${Xs}\"");
+
+        assert_eq!( output, Some("String: Hello Ada, you have 2 items: [1, 2]".into()) );
+    }
+
+    #[test]
+    fn should_eval_pipeline() {
+        let output = run("[[1], [2, 3]] -> map(length) -> fold_left(0, add)");
+
+        assert_eq!( output, Some("Int: 3".into()) );
+    }
+
+    #[test]
+    fn should_eval_method_call() {
+        let output = run("let Xs = [[1], [2, 3]]; Xs.map(length).reverse");
+
+        assert_eq!( output, Some("List( Int: 2, Int: 1 )".into()) );
+    }
+
+    #[test]
+    fn should_type_check_before_compiling() {
+        let mut context = Context::new();
+        context.check_types = true;
+        let tokens = crate::parsing::tokenizer::tokenize("let X = [1, 2]; let Y = X.length; substring(Y, 0, 1)").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context);
+
+        let located = output.unwrap_err().downcast::<trace::Located>().unwrap();
+        assert!( located.error.downcast_ref::<crate::typing::error::TypeError>().is_some() );
+        assert_eq!( (located.line, located.column), (1, 44) );
+        assert!( context.address_map.is_empty() );
+    }
+
+    #[test]
+    fn should_check_let_annotation_at_runtime() {
+        let mut context = Context::new();
+        let tokens = crate::parsing::tokenizer::tokenize("let X : List<Int> = [1, 2.5];").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context);
+
+        assert!( matches!( output.unwrap_err().downcast_ref::<DynamicError>()
+                         , Some(DynamicError::TypeMismatch { expected, observed }) if expected == "Int" && observed == "Number" ) );
+        assert!( context.address_map.is_empty() );
+    }
+
+    #[test]
+    fn should_accept_int_for_number_annotation() {
+        let mut context = Context::new();
+        context.check_types = true;
+        let tokens = crate::parsing::tokenizer::tokenize("let X : Number = 5; X").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context).unwrap();
+
+        assert_eq!( output, Some("Int: 5".into()) );
+    }
+
+    #[test]
+    fn should_catch_raised_value() {
+        let output = run("let Y = 2; try { let X = raise({oops, 1}); Y } catch {error, _} => 0 catch {oops, X} => X");
+
+        assert_eq!( output, Some("Int: 1".into()) );
+    }
+
+    #[test]
+    fn should_surface_uncaught_raise() {
+        let mut context = Context::new();
+        let tokens = crate::parsing::tokenizer::tokenize("try { raise({oops, \"bad\"}) } catch {error, _} => 0").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context).unwrap_err();
+
+        assert!( matches!( output.downcast_ref::<DynamicError>(), Some(DynamicError::Uncaught(RuntimeData::Tuple(_), _)) ) );
+        assert_eq!( output.to_string(), "uncaught raise: {oops, \"bad\"}" );
+    }
+
+    #[test]
+    fn should_catch_raise_from_lambda_called_by_native() {
+        let output = run("try { map([1, 2], fun(X) { raise({bad, X}) }) } catch {bad, X} => X");
+
+        assert_eq!( output, Some("Int: 1".into()) );
+    }
+
+    #[test]
+    fn should_call_lambda_defined_in_try() {
+        let output = run("let Y = 2; try { let F = fun(X) { add(X, Y) }; F(1) } catch _ => 0");
+
+        assert_eq!( output, Some("Int: 3".into()) );
+    }
+
+    #[test]
+    fn should_add_let_frame_to_runtime_errors() {
+        let mut context = Context::new();
+        let input = "let X = 1;\nlet Y = raise(X);";
+        context.source = trace::SourceMap::new(None, input);
+        let tokens = crate::parsing::tokenizer::tokenize(input).unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context).unwrap_err();
+
+        assert!( output.to_string().starts_with("traceback (most recent call last):\n  in let Y (input:2)\n") );
+        assert!( matches!( trace::root(&*output).downcast_ref::<DynamicError>(), Some(DynamicError::Uncaught(RuntimeData::Int(1), _)) ) );
+    }
+
+    #[test]
+    fn should_call_lambda_with_contracts() {
+        let output = run("let F = fun(Xs) requires length(Xs) == 2 ensures R -> R == 2 { length(Xs) }; let 2 = F([1, 2]); map([[1, 2]], F)");
+
+        assert_eq!( output, Some("List( Int: 2 )".into()) );
+    }
+
+    #[test]
+    fn should_fail_lambda_call_that_violates_requires() {
+        let mut context = Context::new();
+        let tokens = crate::parsing::tokenizer::tokenize("let F = fun(X) requires X == 1 { X }; F(2)").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context).unwrap_err();
+
+        assert!( matches!( trace::root(&*output).downcast_ref::<DynamicError>(), Some(DynamicError::Panic(m)) if m.contains("requires X == 1 failed") ) );
+    }
+
+    #[test]
+    fn should_propagate_error_from_called_lambda() {
+        let output = run("let F = fun(X) { let Y = X?; {ok, add(Y, 1)} }; let {ok, 2} = F({ok, 1}); let {error, E} = F({error, no}); E");
+
+        assert_eq!( output, Some("Symbol: no".into()) );
+    }
+
+    #[test]
+    fn should_trace_error_through_nested_lambda_calls() {
+        let mut context = Context::new();
+        let input = "let G = fun(X) {\n    raise(X)\n};\nlet F = fun(X) {\n    let Y = 1;\n    G(X)\n};\nlet Z = F(oops);";
+        context.source = trace::SourceMap::new(None, input);
+        let tokens = crate::parsing::tokenizer::tokenize(input).unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+
+        let output = eval(top, &mut context).unwrap_err();
+
+        assert_eq!( output.to_string(), "traceback (most recent call last):\n  in let Z (input:8)\n  in fun F (input:6)\n  in fun G (input:2)\nuncaught raise: oops" );
+    }
+
+    #[test]
+    fn should_sort_with_comparator_that_is_not_a_total_order() {
+        let output = run("length(sort_by(range(0, 100), fun(A, B) { lt }))");
+
+        assert_eq!( output, Some("Int: 100".into()) );
+    }
+}
@@ -3,5 +3,6 @@ pub mod data;
 
 mod error;
 pub mod pattern_matcher;
+pub mod module;
 
 pub mod evaler;
\ No newline at end of file
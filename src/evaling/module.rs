@@ -0,0 +1,212 @@
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::ast::*;
+use crate::runtime::*;
+use crate::compiling::error::StaticError;
+use crate::compiling::trace::SourceMap;
+use crate::parsing::{tokenizer, parser};
+
+use crate::typing::infer::Env;
+
+use super::data::Context;
+use super::evaler;
+
+// NOTE:  The exported variables of a module along with their types when it was type checked.
+pub struct Module {
+    pub exports : HashMap<String, HeapAddress>,
+    pub types : Env,
+}
+
+// NOTE:  Binds every variable exported by the imported module as Name.Variable in the current namespace.  Each module
+// is only evaluated once no matter how many files import it.
+pub fn import( import : &Import, context : &mut Context ) -> Result<(), Box<dyn std::error::Error>> {
+    if context.imported.contains(&import.name) {
+        return Err(Box::new(StaticError::DuplicateImport(import.name.clone())));
+    }
+
+    let path = canonicalize(&context.resolver.resolve(&import.path, context.current_file.as_deref())?)?;
+
+    if let Some(start) = context.loading.iter().position(|p| *p == path) {
+        let mut cycle = context.loading[start..].iter().map(|p| p.display().to_string()).collect::<Vec<_>>();
+        cycle.push(path.display().to_string());
+        return Err(Box::new(StaticError::CyclicImport(cycle)));
+    }
+
+    if !context.modules.contains_key(&path) {
+        let module = load(&path, context)?;
+        context.modules.insert(path.clone(), module);
+    }
+
+    context.imported.insert(import.name.clone());
+    let prefix = format!("{}.", import.name);
+    let module = &context.modules[&path];
+    for (name, address) in &module.exports {
+        context.address_map.insert(format!("{}{}", prefix, name), *address);
+    }
+    for (name, scheme) in &module.types {
+        context.types.insert(format!("{}{}", prefix, name), scheme.clone());
+    }
+
+    Ok(())
+}
+
+// NOTE:  Evaluates a file as the main program.  Its imports are relative to the file.
+pub fn run_file<P : AsRef<Path>>( path : P, context : &mut Context ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let path = canonicalize(path.as_ref())?;
+    let (top, source) = read(&path)?;
+
+    let outer_file = context.current_file.replace(path.clone());
+    let outer_source = std::mem::replace(&mut context.source, source);
+    context.loading.push(path);
+    let result = evaler::eval(top, context);
+    context.loading.pop();
+    context.current_file = outer_file;
+    context.source = outer_source;
+
+    result
+}
+
+fn canonicalize( path : &Path ) -> Result<PathBuf, StaticError> {
+    path.canonicalize().map_err(|e| StaticError::CannotReadModule { path: path.display().to_string(), reason: e.to_string() })
+}
+
+fn read( path : &Path ) -> Result<(Top, SourceMap), StaticError> {
+    let name = || path.display().to_string();
+    let input = std::fs::read_to_string(path).map_err(|e| StaticError::CannotReadModule { path: name(), reason: e.to_string() })?;
+    let tokens = tokenizer::tokenize(&input).map_err(|e| StaticError::CannotParseModule { path: name(), reason: e.to_string() })?;
+    let top = parser::parse(&tokens).map_err(|e| StaticError::CannotParseModule { path: name(), reason: format!("{:?}", e) })?;
+    Ok((top, SourceMap::new(Some(path.to_path_buf()), &input)))
+}
+
+// NOTE:  Each module is evaluated in its own namespace so that its variables can't collide with the importer's.  The
+// heap and natives (along with the lambdas compiled so far) are shared so exported data stays valid after the module is done.
+fn load( path : &Path, context : &mut Context ) -> Result<Module, Box<dyn std::error::Error>> {
+    let (top, source) = read(path)?;
+    let exports = top.exports.clone();
+
+    let outer_map = std::mem::take(&mut context.address_map);
+    let outer_types = std::mem::take(&mut context.types);
+    let outer_imported = std::mem::take(&mut context.imported);
+    let outer_file = context.current_file.replace(path.to_path_buf());
+    let outer_source = std::mem::replace(&mut context.source, source);
+    context.loading.push(path.to_path_buf());
+
+    let result = evaler::eval(top, context);
+
+    context.loading.pop();
+    context.current_file = outer_file;
+    context.source = outer_source;
+    let module_map = std::mem::replace(&mut context.address_map, outer_map);
+    let module_types = std::mem::replace(&mut context.types, outer_types);
+    context.imported = outer_imported;
+
+    result?;
+
+    let exports = match exports {
+        Some(names) => names.into_iter().map(|name| match module_map.get(&name) {
+            Some(address) => Ok((name, *address)),
+            None => Err(StaticError::ExportNotDefined(name)),
+        }).collect::<Result<HashMap<_, _>, _>>()?,
+        // NOTE:  Without an export list everything the module defines is visible, but not the modules it imported.
+        None => module_map.into_iter().filter(|(name, _)| !name.contains('.')).collect(),
+    };
+    let types = module_types.into_iter().filter(|(name, _)| exports.contains_key(name)).collect();
+
+    Ok(Module { exports, types })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn dir(name : &str, files : &[(&str, &str)]) -> TempDir {
+        TempDir::new(&format!("module_{}", name), files)
+    }
+
+    #[test]
+    fn should_access_exported_variable() {
+        let dir = dir("exported", &[ ("main.mint", "import \"lib/util.mint\" as U; U.Xs.length")
+                                   , ("lib/util.mint", "export Xs; let Xs = [1, 2, 3]; let Hidden = 1;")
+                                   ]);
+
+        let output = run_file(dir.join("main.mint"), &mut Context::new()).unwrap();
+
+        assert_eq!( output, Some("Int: 3".into()) );
+    }
+
+    #[test]
+    fn should_hide_unexported_variable() {
+        let dir = dir("hidden", &[ ("main.mint", "import \"util.mint\" as U; U.Hidden")
+                                 , ("util.mint", "export Xs; let Xs = [1, 2, 3]; let Hidden = 1;")
+                                 ]);
+
+        let output = run_file(dir.join("main.mint"), &mut Context::new());
+
+        assert!( matches!( output.unwrap_err().downcast_ref::<StaticError>(), Some(StaticError::VariableNotDefined(x)) if x == "U.Hidden" ) );
+    }
+
+    #[test]
+    fn should_load_shared_module_once() {
+        let dir = dir("shared", &[ ("main.mint", "import \"a.mint\" as A; import \"b.mint\" as B; A.X == B.X")
+                                 , ("a.mint", "import \"c.mint\" as C; let X = C.X;")
+                                 , ("b.mint", "import \"c.mint\" as C; let X = C.X;")
+                                 , ("c.mint", "let X = [1];")
+                                 ]);
+        let mut context = Context::new();
+
+        let output = run_file(dir.join("main.mint"), &mut context).unwrap();
+
+        assert_eq!( output, Some("Symbol: true".into()) );
+        assert_eq!( context.modules.len(), 3 );
+    }
+
+    #[test]
+    fn should_detect_cyclic_import() {
+        let dir = dir("cyclic", &[ ("main.mint", "import \"a.mint\" as A; 1")
+                                 , ("a.mint", "import \"b.mint\" as B;")
+                                 , ("b.mint", "import \"a.mint\" as A;")
+                                 ]);
+
+        let output = run_file(dir.join("main.mint"), &mut Context::new());
+
+        assert!( matches!( output.unwrap_err().downcast_ref::<StaticError>(), Some(StaticError::CyclicImport(paths)) if paths.len() == 3 ) );
+    }
+
+    #[test]
+    fn should_reject_undefined_export() {
+        let dir = dir("undefined_export", &[ ("main.mint", "import \"a.mint\" as A; 1")
+                                           , ("a.mint", "export Missing; let X = 1;")
+                                           ]);
+
+        let output = run_file(dir.join("main.mint"), &mut Context::new());
+
+        assert!( matches!( output.unwrap_err().downcast_ref::<StaticError>(), Some(StaticError::ExportNotDefined(x)) if x == "Missing" ) );
+    }
+
+    #[test]
+    fn should_import_through_resolver() {
+        let dir = dir("resolver", &[ ("app/main.mint", "import \"strings.mint\" as S; S.X")
+                                   , ("lib/strings.mint", "let X = 1;")
+                                   ]);
+        let mut context = Context::new();
+        context.resolver.add_search_path(dir.join("lib"));
+
+        let output = run_file(dir.join("app/main.mint"), &mut context).unwrap();
+
+        assert_eq!( output, Some("Int: 1".into()) );
+    }
+
+    #[test]
+    fn should_reject_duplicate_import_of_empty_module() {
+        let dir = dir("duplicate_empty", &[ ("main.mint", "import \"empty.mint\" as E; import \"empty.mint\" as E; 1")
+                                          , ("empty.mint", "")
+                                          ]);
+
+        let output = run_file(dir.join("main.mint"), &mut Context::new());
+
+        assert!( matches!( output.unwrap_err().downcast_ref::<StaticError>(), Some(StaticError::DuplicateImport(x)) if x == "E" ) );
+    }
+}
@@ -1,8 +1,10 @@
 
 use std::collections::HashMap;
 
-use crate::ast::Pat;
+use crate::ast::{Pat, Lit, Bound, RangeEnd};
+use crate::bignum::{BigInt, Rational};
 use crate::runtime::*;
+use crate::compiling::error::StaticError;
 
 use super::error::*;
 
@@ -17,17 +19,107 @@ pub struct BoundData {
     pub data : RuntimeData,
 }
 
-pub fn pattern_match( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
+// NOTE:  A pattern the way it is matched at runtime (see intern).  Symbols are ids so that matching compares them
+// directly, and map keys are values so that matching searches the map the same way get does.  Pat stays free of
+// runtime state because it comes straight from the parser.
+#[derive(Debug, Clone)]
+pub enum InternedPat {
+    Wild,
+    Number(f64),
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Char(char),
+    Range(Option<Bound>, Option<RangeEnd>),
+    String(String),
+    Symbol(SymbolId),
+    List(Vec<InternedPat>, Option<Box<InternedPat>>, Vec<InternedPat>),
+    Tuple(Vec<InternedPat>),
+    Variable(String),
+    At(String, Box<InternedPat>),
+    Or(Vec<InternedPat>),
+    StringConcat(Box<InternedPat>, Box<InternedPat>),
+    Pin(String),
+    // NOTE:  The map may contain other keys.
+    Map(Vec<(RuntimeData, InternedPat)>),
+}
+
+// NOTE:  Pins are resolved to their values before matching, so pins maps a pinned variable name to the value that
+// the pin pattern is compared against (see runtime::pin_eq).
+pub fn pattern_match( pattern : &InternedPat, data : &RuntimeData, heap : &Heap, pins : &HashMap<String, RuntimeData> ) -> MatchResult {
     use MatchResult::*;
-    use std::iter::{zip, repeat};
+    use std::iter::zip;
     match (pattern, data) {
-        (Pat::Wild, _) => Env(vec![]),
-        (Pat::Variable(a), b) => Env(vec![BoundData{ name: a.clone(), data: b.clone()}]),
-        (Pat::Number(a), RuntimeData::Number(b)) if a == b => Env(vec![]),
-        (Pat::String(a), RuntimeData::String(b)) if a == b => Env(vec![]),
-        (Pat::Symbol(a), RuntimeData::Symbol(b)) if a == b => Env(vec![]),
-        (Pat::At(name, pat), b) => {
-            match pattern_match(pat, b) {
+        (InternedPat::Wild, _) => Env(vec![]),
+        (pattern, RuntimeData::Address(address)) => {
+            match heap.get(*address) {
+                Some(data) => pattern_match(pattern, data, heap, pins),
+                None => Fatal(RuntimeError::CannotFindHeapAddress),
+            }
+        },
+        (InternedPat::Pin(name), b) => {
+            match pins.get(name) {
+                Some(value) if pin_eq(heap, value, b) => Env(vec![]),
+                Some(_) => NoMatch,
+                None => Fatal(RuntimeError::VariableNotFound(name.clone())),
+            }
+        },
+        (InternedPat::Variable(a), b) => Env(vec![BoundData{ name: a.clone(), data: b.clone()}]),
+        // NOTE:  NaN is not equal to anything (including NaN), so it never matches a Number pattern.
+        (InternedPat::Number(a), RuntimeData::Number(b)) if a == b => Env(vec![]),
+        // NOTE:  Ints only match Int patterns, so 1 does not match 1.0.
+        (InternedPat::Int(a), RuntimeData::Int(b)) if a == b => Env(vec![]),
+        (InternedPat::BigInt(a), RuntimeData::BigInt(b)) if a == b => Env(vec![]),
+        (InternedPat::Rational(a), RuntimeData::Rational(b)) if a == b => Env(vec![]),
+        // NOTE:  Bounds are compared exactly with every kind of number.  NaN is unordered with respect to every bound,
+        // so every comparison with it is false.
+        (InternedPat::Range(start, end), b @ (RuntimeData::Number(_) | RuntimeData::Int(_) | RuntimeData::BigInt(_) | RuntimeData::Rational(_))) => {
+            use std::cmp::Ordering::*;
+            let cmp = |bound : &Bound| cmp_num(b, &bound_data(bound));
+            let after_start = match start {
+                Some(s) => matches!(cmp(s), Some(Greater | Equal)),
+                None => !matches!(b, RuntimeData::Number(x) if x.is_nan()),
+            };
+            let before_end = match end {
+                Some(RangeEnd::Inclusive(e)) => matches!(cmp(e), Some(Less | Equal)),
+                Some(RangeEnd::Exclusive(e)) => matches!(cmp(e), Some(Less)),
+                None => true,
+            };
+            if after_start && before_end {
+                Env(vec![])
+            }
+            else {
+                NoMatch
+            }
+        },
+        (InternedPat::String(a), RuntimeData::String(b)) if a == b => Env(vec![]),
+        (InternedPat::Char(a), RuntimeData::Char(b)) if a == b => Env(vec![]),
+        (InternedPat::Symbol(a), RuntimeData::Symbol(b)) if a == b => Env(vec![]),
+        // NOTE:  Alternatives are tried in order and the first one that matches wins.
+        (InternedPat::Or(ps), b) => {
+            for p in ps {
+                match pattern_match(p, b, heap, pins) {
+                    NoMatch => { },
+                    result => { return result; },
+                }
+            }
+            NoMatch
+        },
+        (InternedPat::StringConcat(a, b), RuntimeData::String(s)) => {
+            match (&**a, &**b) {
+                (InternedPat::String(prefix), rest) => match s.strip_prefix(prefix.as_str()) {
+                    Some(r) => pattern_match(rest, &RuntimeData::String(r.into()), heap, pins),
+                    None => NoMatch,
+                },
+                (rest, InternedPat::String(suffix)) => match s.strip_suffix(suffix.as_str()) {
+                    Some(r) => pattern_match(rest, &RuntimeData::String(r.into()), heap, pins),
+                    None => NoMatch,
+                },
+                _ => Fatal(RuntimeError::StringConcatPatternWithoutLiteral),
+            }
+        },
+        (InternedPat::At(name, pat), b) => {
+            match pattern_match(pat, b, heap, pins) {
                 NoMatch => NoMatch,
                 Fatal(e) => Fatal(e),
                 Env(mut env) => {
@@ -39,11 +131,11 @@ pub fn pattern_match( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
                 }
             }
         },
-        (Pat::Tuple(a), RuntimeData::Tuple(b)) if a.len() != b.len() => NoMatch, 
-        (Pat::Tuple(a), RuntimeData::Tuple(b)) => {
+        (InternedPat::Tuple(a), RuntimeData::Tuple(b)) if a.len() != b.len() => NoMatch, 
+        (InternedPat::Tuple(a), RuntimeData::Tuple(b)) => {
             let mut all = HashMap::new();
             for (pat, data) in zip(a, b) {
-                match pattern_match(pat, data) {
+                match pattern_match(pat, data, heap, pins) {
                     NoMatch => { return NoMatch; },
                     Fatal(e) => { return Fatal(e); },
                     Env(env) => {
@@ -59,13 +151,13 @@ pub fn pattern_match( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
             Env(all.into_iter().map(|kvp| BoundData { name: kvp.0, data: kvp.1 }).collect::<Vec<_>>())
         },
         // NOTE:  If there exists more patterns than items in the target list, then indicate NoMatch.
-        (Pat::List(a, _), RuntimeData::List(b)) if a.len() > b.len() => NoMatch,
-        // NOTE:  If there is no 'rest' pattern, then the lengths need to match.
-        (Pat::List(a, None), RuntimeData::List(b)) if a.len() != b.len() => NoMatch,
-        (Pat::List(a, None), RuntimeData::List(b)) => {
+        (InternedPat::List(a, _, c), RuntimeData::List(b)) if a.len() + c.len() > b.len() => NoMatch,
+        // NOTE:  If there is no 'rest' pattern, then the lengths need to match (and there is no suffix).
+        (InternedPat::List(a, None, _), RuntimeData::List(b)) if a.len() != b.len() => NoMatch,
+        (InternedPat::List(a, None, _), RuntimeData::List(b)) => {
             let mut all = HashMap::new();
             for (pat, data) in zip(a, b) {
-                match pattern_match(pat, data) {
+                match pattern_match(pat, data, heap, pins) {
                     NoMatch => { return NoMatch; },
                     Fatal(e) => { return Fatal(e); },
                     Env(env) => {
@@ -80,13 +172,15 @@ pub fn pattern_match( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
             }
             Env(all.into_iter().map(|kvp| BoundData { name: kvp.0, data: kvp.1 }).collect::<Vec<_>>())
         },
-        (Pat::List(a, Some(rest)), RuntimeData::List(b)) => {
-            let p = a.iter().map(|x| Some(x)).chain(repeat(None));
-            let (matches, rest_data) : (Vec<_>, Vec<_>) = zip(p, b).partition(|(a, _)| a.is_some());
+        // NOTE:  The prefix patterns match the front of the list, the suffix patterns match the back of the list,
+        // and the 'rest' pattern matches whatever is left in the middle (which may be empty).
+        (InternedPat::List(a, Some(rest), c), RuntimeData::List(b)) => {
+            let suffix_start = b.len() - c.len();
+            let matches = zip(a, &b[..a.len()]).chain(zip(c, &b[suffix_start..]));
 
             let mut all = HashMap::new();
             for (pat, data) in matches {
-                match pattern_match(pat.unwrap(), data) {
+                match pattern_match(pat, data, heap, pins) {
                     NoMatch => { return NoMatch; },
                     Fatal(e) => { return Fatal(e); },
                     Env(env) => {
@@ -100,9 +194,9 @@ pub fn pattern_match( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
                 }
             }
 
-            let rest_data = rest_data.into_iter().map(|(_, b)| b.clone()).collect();
+            let rest_data = b[a.len()..suffix_start].to_vec();
 
-            match pattern_match(rest, &RuntimeData::List(rest_data)) {
+            match pattern_match(rest, &RuntimeData::List(rest_data), heap, pins) {
                 NoMatch => { return NoMatch; },
                 Fatal(e) => { return Fatal(e); },
                 Env(env) => {
@@ -116,17 +210,444 @@ pub fn pattern_match( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
             }
             Env(all.into_iter().map(|kvp| BoundData { name: kvp.0, data: kvp.1 }).collect::<Vec<_>>())
         },
+        // NOTE:  Every key in the pattern needs to be in the map, but the map can also contain other keys.
+        (InternedPat::Map(kvps), RuntimeData::Map(entries)) => {
+            let mut all = HashMap::new();
+            for (key, pat) in kvps {
+                let data = match map_search(heap, entries, key) {
+                    Some(Ok(i)) => &entries[i].1,
+                    Some(Err(_)) => { return NoMatch; },
+                    None => { return Fatal(RuntimeError::CannotFindHeapAddress); },
+                };
+                match pattern_match(pat, data, heap, pins) {
+                    NoMatch => { return NoMatch; },
+                    Fatal(e) => { return Fatal(e); },
+                    Env(env) => {
+                        for e in env {
+                            if all.contains_key(&e.name) {
+                                return Fatal(RuntimeError::CannotSetBoundVariable(e.name));
+                            }
+                            all.insert(e.name, e.data);
+                        }
+                    },
+                }
+            }
+            Env(all.into_iter().map(|kvp| BoundData { name: kvp.0, data: kvp.1 }).collect::<Vec<_>>())
+        },
         _ => NoMatch,
     }
 }
 
+// NOTE:  Interns every symbol in the pattern ahead of time so that matching compares ids instead of looking the
+// symbol's name up in the heap on every match.  Map keys are turned into values at the same time.
+pub fn intern( pattern : &Pat, symbols : &mut SymbolTable ) -> Result<InternedPat, StaticError> {
+    fn all( ps : &[Pat], symbols : &mut SymbolTable ) -> Result<Vec<InternedPat>, StaticError> {
+        ps.iter().map(|p| intern(p, symbols)).collect()
+    }
+    fn boxed( p : &Pat, symbols : &mut SymbolTable ) -> Result<Box<InternedPat>, StaticError> {
+        Ok(Box::new(intern(p, symbols)?))
+    }
+    Ok(match pattern {
+        Pat::Wild => InternedPat::Wild,
+        Pat::Number(x) => InternedPat::Number(*x),
+        Pat::Int(x) => InternedPat::Int(*x),
+        Pat::BigInt(x) => InternedPat::BigInt(x.clone()),
+        Pat::Rational(x) => InternedPat::Rational(x.clone()),
+        Pat::Char(x) => InternedPat::Char(*x),
+        Pat::Range(start, end) => InternedPat::Range(*start, *end),
+        Pat::String(x) => InternedPat::String(x.clone()),
+        Pat::Symbol(name) => InternedPat::Symbol(symbols.intern(name)),
+        Pat::List(prefix, rest, suffix) => {
+            let rest = match rest {
+                Some(r) => Some(boxed(r, symbols)?),
+                None => None,
+            };
+            InternedPat::List(all(prefix, symbols)?, rest, all(suffix, symbols)?)
+        },
+        Pat::Tuple(ps) => InternedPat::Tuple(all(ps, symbols)?),
+        Pat::Variable(name) => InternedPat::Variable(name.clone()),
+        Pat::At(name, p) => InternedPat::At(name.clone(), boxed(p, symbols)?),
+        Pat::Or(ps) => InternedPat::Or(all(ps, symbols)?),
+        Pat::StringConcat(a, b) => InternedPat::StringConcat(boxed(a, symbols)?, boxed(b, symbols)?),
+        Pat::Pin(name) => InternedPat::Pin(name.clone()),
+        Pat::Map(kvps) => {
+            let mut keys = vec![];
+            for (k, p) in kvps {
+                let key = constant(k, symbols).ok_or(StaticError::MapPatternKeyNotConstant)?;
+                keys.push((key, intern(p, symbols)?));
+            }
+            InternedPat::Map(keys)
+        },
+    })
+}
+
+// NOTE:  The value of a constant literal (see compiler::check_pattern), or None when it isn't a constant.
+fn constant( lit : &Lit, symbols : &mut SymbolTable ) -> Option<RuntimeData> {
+    Some(match lit {
+        Lit::Number(x) => RuntimeData::Number(*x),
+        Lit::Int(x) => RuntimeData::Int(*x),
+        Lit::BigInt(x) => RuntimeData::BigInt(x.clone()),
+        Lit::Rational(x) => RuntimeData::Rational(x.clone()),
+        Lit::String(x) => RuntimeData::String(x.clone()),
+        Lit::Char(x) => RuntimeData::Char(*x),
+        Lit::Symbol(x) => RuntimeData::Symbol(symbols.intern(x)),
+        Lit::List(xs) => RuntimeData::List(xs.iter().map(|x| constant(x, symbols)).collect::<Option<_>>()?),
+        Lit::Tuple(xs) => RuntimeData::Tuple(xs.iter().map(|x| constant(x, symbols)).collect::<Option<_>>()?),
+        Lit::Variable(_) | Lit::Map(_) | Lit::Lambda(_) => { return None; },
+    })
+}
+
+fn bound_data( bound : &Bound ) -> RuntimeData {
+    match bound {
+        Bound::Int(x) => RuntimeData::Int(*x),
+        Bound::Number(x) => RuntimeData::Number(*x),
+    }
+}
+
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn m( pattern : &Pat, data : &RuntimeData ) -> MatchResult {
+        m_in(pattern, data, &mut Heap::new(), &HashMap::new())
+    }
+
+    fn m_in( pattern : &Pat, data : &RuntimeData, heap : &mut Heap, pins : &HashMap<String, RuntimeData> ) -> MatchResult {
+        let pattern = intern(pattern, heap.symbols_mut()).unwrap();
+        pattern_match(&pattern, data, heap, pins)
+    }
+
     #[test]
     fn todo() {
 
     }
-}
\ No newline at end of file
+
+    fn bound(result : MatchResult) -> Vec<(String, RuntimeData)> {
+        match result {
+            MatchResult::Env(env) => {
+                let mut ret = env.into_iter().map(|b| (b.name, b.data)).collect::<Vec<_>>();
+                ret.sort_by(|a, b| a.0.cmp(&b.0));
+                ret
+            },
+            MatchResult::NoMatch => panic!("expected match but found NoMatch"),
+            MatchResult::Fatal(e) => panic!("expected match but found Fatal: {}", e),
+        }
+    }
+
+    #[test]
+    fn or_pattern_should_match_first_matching_alternative() {
+        let pattern = Pat::Or(vec![ Pat::Tuple(vec![Pat::Variable("X".into())])
+                                  , Pat::List(vec![Pat::Variable("X".into())], None, vec![])
+                                  ]);
+        let data = RuntimeData::List(vec![RuntimeData::Number(1.0)]);
+
+        let output = bound(m(&pattern, &data));
+
+        assert_eq!( output, vec![("X".into(), RuntimeData::Number(1.0))] );
+    }
+
+    #[test]
+    fn or_pattern_should_not_match_when_no_alternative_matches() {
+        let mut heap = Heap::new();
+        let pattern = Pat::Or(vec![ Pat::Symbol("a".into()), Pat::Symbol("b".into()) ]);
+        let data = RuntimeData::Symbol(heap.symbols_mut().intern("c"));
+
+        let output = m_in(&pattern, &data, &mut heap, &HashMap::new());
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn range_pattern_should_match_numbers_within_bounds() {
+        let inclusive = Pat::Range(Some(Bound::Number(200.0)), Some(RangeEnd::Inclusive(Bound::Number(299.0))));
+        let exclusive = Pat::Range(Some(Bound::Number(200.0)), Some(RangeEnd::Exclusive(Bound::Number(300.0))));
+        let open = Pat::Range(Some(Bound::Number(0.0)), None);
+
+        assert!( matches!( m(&inclusive, &RuntimeData::Number(299.0)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&exclusive, &RuntimeData::Number(299.5)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&open, &RuntimeData::Number(0.0)), MatchResult::Env(_) ) );
+    }
+
+    #[test]
+    fn range_pattern_should_not_match_numbers_outside_bounds() {
+        let inclusive = Pat::Range(Some(Bound::Number(200.0)), Some(RangeEnd::Inclusive(Bound::Number(299.0))));
+        let exclusive = Pat::Range(Some(Bound::Number(200.0)), Some(RangeEnd::Exclusive(Bound::Number(300.0))));
+        let upper = Pat::Range(None, Some(RangeEnd::Exclusive(Bound::Number(0.0))));
+
+        assert!( matches!( m(&inclusive, &RuntimeData::Number(299.5)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&exclusive, &RuntimeData::Number(300.0)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&upper, &RuntimeData::Number(0.0)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&upper, &RuntimeData::String("-1".into())), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn nan_should_not_match_number_or_range_patterns() {
+        let everything = Pat::Range(None, None);
+
+        assert!( matches!( m(&everything, &RuntimeData::Number(f64::NAN)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&Pat::Number(f64::NAN), &RuntimeData::Number(f64::NAN)), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn string_concat_pattern_should_bind_remainder_after_prefix() {
+        let pattern = Pat::StringConcat(Box::new(Pat::String("GET ".into())), Box::new(Pat::Variable("Path".into())));
+        let data = RuntimeData::String("GET /index.html".into());
+
+        let output = bound(m(&pattern, &data));
+
+        assert_eq!( output, vec![("Path".into(), RuntimeData::String("/index.html".into()))] );
+    }
+
+    #[test]
+    fn string_concat_pattern_should_bind_remainder_before_suffix() {
+        let pattern = Pat::StringConcat(Box::new(Pat::Variable("Name".into())), Box::new(Pat::String(".json".into())));
+        let data = RuntimeData::String("config.json".into());
+
+        let output = bound(m(&pattern, &data));
+
+        assert_eq!( output, vec![("Name".into(), RuntimeData::String("config".into()))] );
+    }
+
+    #[test]
+    fn string_concat_pattern_should_not_match_missing_prefix() {
+        let pattern = Pat::StringConcat(Box::new(Pat::String("GET ".into())), Box::new(Pat::Wild));
+        let data = RuntimeData::String("POST /index.html".into());
+
+        let output = m(&pattern, &data);
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    fn numbers(ns : &[f64]) -> RuntimeData {
+        RuntimeData::List(ns.iter().map(|n| RuntimeData::Number(*n)).collect())
+    }
+
+    fn var(name : &str) -> Pat {
+        Pat::Variable(name.into())
+    }
+
+    #[test]
+    fn list_pattern_should_not_match_when_prefix_and_suffix_exceed_list_length() {
+        let pattern = Pat::List(vec![var("A"), var("B")], Some(Box::new(Pat::Wild)), vec![var("C")]);
+
+        let output = m(&pattern, &numbers(&[1.0, 2.0]));
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn list_pattern_without_rest_should_not_match_different_length() {
+        let pattern = Pat::List(vec![var("A"), var("B")], None, vec![]);
+
+        let output = m(&pattern, &numbers(&[1.0, 2.0, 3.0]));
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn list_pattern_should_bind_suffix() {
+        let pattern = Pat::List(vec![], Some(Box::new(var("Init"))), vec![var("Last")]);
+
+        let output = bound(m(&pattern, &numbers(&[1.0, 2.0, 3.0])));
+
+        assert_eq!( output, vec![ ("Init".into(), numbers(&[1.0, 2.0]))
+                                , ("Last".into(), RuntimeData::Number(3.0))
+                                ] );
+    }
+
+    #[test]
+    fn list_pattern_should_bind_prefix_middle_and_suffix() {
+        let pattern = Pat::List(vec![var("First")], Some(Box::new(var("Middle"))), vec![var("Last")]);
+
+        let output = bound(m(&pattern, &numbers(&[1.0, 2.0, 3.0, 4.0])));
+
+        assert_eq!( output, vec![ ("First".into(), RuntimeData::Number(1.0))
+                                , ("Last".into(), RuntimeData::Number(4.0))
+                                , ("Middle".into(), numbers(&[2.0, 3.0]))
+                                ] );
+    }
+
+    #[test]
+    fn list_pattern_should_bind_empty_middle() {
+        let pattern = Pat::List(vec![var("First")], Some(Box::new(var("Middle"))), vec![var("Last")]);
+
+        let output = bound(m(&pattern, &numbers(&[1.0, 2.0])));
+
+        assert_eq!( output, vec![ ("First".into(), RuntimeData::Number(1.0))
+                                , ("Last".into(), RuntimeData::Number(2.0))
+                                , ("Middle".into(), numbers(&[]))
+                                ] );
+    }
+
+    #[test]
+    fn pin_pattern_should_match_equal_value() {
+        let mut heap = Heap::new();
+        let address = heap.insert_new(numbers(&[1.0, 2.0]));
+        let pins = HashMap::from([("X".to_string(), RuntimeData::Address(address))]);
+        let pattern = Pat::Tuple(vec![Pat::Pin("X".into()), var("Y")]);
+        let data = RuntimeData::Tuple(vec![numbers(&[1.0, 2.0]), RuntimeData::Number(3.0)]);
+
+        let output = bound(m_in(&pattern, &data, &mut heap, &pins));
+
+        assert_eq!( output, vec![("Y".into(), RuntimeData::Number(3.0))] );
+    }
+
+    #[test]
+    fn pin_pattern_should_not_match_different_value() {
+        let pins = HashMap::from([("X".to_string(), RuntimeData::Number(1.0))]);
+
+        let output = m_in(&Pat::Pin("X".into()), &RuntimeData::Number(2.0), &mut Heap::new(), &pins);
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn pin_pattern_should_not_match_nan() {
+        let pins = HashMap::from([("X".to_string(), RuntimeData::Number(f64::NAN))]);
+
+        let output = m_in(&Pat::Pin("X".into()), &RuntimeData::Number(f64::NAN), &mut Heap::new(), &pins);
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn pattern_should_match_through_heap_address() {
+        let mut heap = Heap::new();
+        let address = heap.insert_new(numbers(&[1.0]));
+        let pattern = Pat::List(vec![var("A")], None, vec![]);
+
+        let output = bound(m_in(&pattern, &RuntimeData::Address(address), &mut heap, &HashMap::new()));
+
+        assert_eq!( output, vec![("A".into(), RuntimeData::Number(1.0))] );
+    }
+
+    #[test]
+    fn symbol_pattern_should_match_interned_symbol() {
+        let mut heap = Heap::new();
+        let data = RuntimeData::Symbol(heap.symbols_mut().intern("ok"));
+
+        assert!( matches!( m_in(&Pat::Symbol("ok".into()), &data, &mut heap, &HashMap::new()), MatchResult::Env(_) ) );
+        assert!( matches!( m_in(&Pat::Symbol("error".into()), &data, &mut heap, &HashMap::new()), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn intern_should_resolve_nested_symbol_patterns() {
+        let mut heap = Heap::new();
+        let pattern = Pat::Tuple(vec![Pat::Symbol("ok".into()), var("A")]);
+
+        let interned = intern(&pattern, heap.symbols_mut()).unwrap();
+        let ok = heap.symbols().get("ok").unwrap();
+
+        assert!( matches!( &interned, InternedPat::Tuple(ps) if matches!( ps[0], InternedPat::Symbol(x) if x == ok ) ) );
+
+        let data = RuntimeData::Tuple(vec![RuntimeData::Symbol(ok), RuntimeData::Number(1.0)]);
+        let output = bound(pattern_match(&interned, &data, &heap, &HashMap::new()));
+
+        assert_eq!( output, vec![("A".into(), RuntimeData::Number(1.0))] );
+    }
+
+    #[test]
+    fn map_pattern_should_extract_given_keys() {
+        let mut heap = Heap::new();
+        let a = heap.symbols_mut().intern("a");
+        let data = RuntimeData::Map(vec![ (RuntimeData::Number(1.0), RuntimeData::String("one".into()))
+                                        , (RuntimeData::Symbol(a), RuntimeData::Number(2.0))
+                                        ]);
+        let pattern = Pat::Map(vec![(Lit::Symbol("a".into()), var("A"))]);
+
+        let output = bound(m_in(&pattern, &data, &mut heap, &HashMap::new()));
+
+        assert_eq!( output, vec![("A".into(), RuntimeData::Number(2.0))] );
+    }
+
+    #[test]
+    fn interned_map_pattern_should_find_keys_like_get() {
+        let mut heap = Heap::new();
+        let pattern = Pat::Map(vec![ (Lit::Tuple(vec![Lit::Symbol("a".into()), Lit::Int(1)]), var("A"))
+                                   , (Lit::Number(2.0), var("B"))
+                                   ]);
+        let pattern = intern(&pattern, heap.symbols_mut()).unwrap();
+        let a = heap.symbols().get("a").unwrap();
+        let mut data = vec![];
+        for i in 0..10 {
+            map_insert(&heap, &mut data, RuntimeData::Int(i), RuntimeData::Int(i)).unwrap();
+        }
+        map_insert(&heap, &mut data, RuntimeData::Number(2.0), RuntimeData::Number(2.0)).unwrap();
+        map_insert(&heap, &mut data, RuntimeData::Tuple(vec![RuntimeData::Symbol(a), RuntimeData::Int(1)]), RuntimeData::Int(3)).unwrap();
+
+        assert!( matches!( pattern, InternedPat::Map(_) ) );
+
+        let output = bound(pattern_match(&pattern, &RuntimeData::Map(data), &heap, &HashMap::new()));
+
+        assert_eq!( output, vec![("A".into(), RuntimeData::Int(3)), ("B".into(), RuntimeData::Number(2.0))] );
+    }
+
+    #[test]
+    fn intern_should_reject_non_constant_map_keys() {
+        let pattern = Pat::Map(vec![(Lit::Variable("X".into()), Pat::Wild)]);
+
+        let output = intern(&pattern, &mut SymbolTable::new());
+
+        assert!( matches!( output, Err(StaticError::MapPatternKeyNotConstant) ) );
+    }
+
+    #[test]
+    fn map_pattern_should_not_match_missing_key() {
+        let data = RuntimeData::Map(vec![(RuntimeData::Number(1.0), RuntimeData::Number(2.0))]);
+        let pattern = Pat::Map(vec![(Lit::Number(2.0), Pat::Wild)]);
+
+        let output = m(&pattern, &data);
+
+        assert!( matches!( output, MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn int_pattern_should_not_match_number() {
+        assert!( matches!( m(&Pat::Int(1), &RuntimeData::Int(1)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&Pat::Int(1), &RuntimeData::Number(1.0)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&Pat::Number(1.0), &RuntimeData::Int(1)), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn range_pattern_should_match_int() {
+        let pattern = Pat::Range(Some(Bound::Number(200.0)), Some(RangeEnd::Exclusive(Bound::Int(300))));
+
+        assert!( matches!( m(&pattern, &RuntimeData::Int(200)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&pattern, &RuntimeData::Int(299)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&pattern, &RuntimeData::Int(300)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&Pat::Range(None, Some(RangeEnd::Inclusive(Bound::Number(0.5)))), &RuntimeData::Int(1)), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn range_pattern_should_keep_int_bounds_exact() {
+        let pattern = Pat::Range(Some(Bound::Int(9007199254740993)), None);
+
+        assert!( matches!( m(&pattern, &RuntimeData::Int(9007199254740993)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&pattern, &RuntimeData::Int(9007199254740992)), MatchResult::NoMatch ) );
+        assert!( matches!( m(&pattern, &RuntimeData::Number(9007199254740992.0)), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn range_pattern_should_compare_exact_numbers_exactly() {
+        let pattern = Pat::Range(Some(Bound::Int(1)), Some(RangeEnd::Exclusive(Bound::Number(2.0))));
+        let big = crate::bignum::BigInt::parse("9223372036854775808").unwrap();
+        let above = Pat::Range(Some(Bound::Int(i64::MAX)), None);
+
+        assert!( matches!( m(&pattern, &RuntimeData::Rational(crate::bignum::Rational::parse("3/2").unwrap())), MatchResult::Env(_) ) );
+        assert!( matches!( m(&pattern, &RuntimeData::Rational(crate::bignum::Rational::parse("2").unwrap())), MatchResult::NoMatch ) );
+        assert!( matches!( m(&pattern, &RuntimeData::BigInt(crate::bignum::BigInt::from_i64(1))), MatchResult::Env(_) ) );
+        assert!( matches!( m(&above, &RuntimeData::BigInt(big.clone())), MatchResult::Env(_) ) );
+        assert!( matches!( m(&above, &RuntimeData::BigInt(-&big)), MatchResult::NoMatch ) );
+    }
+
+    #[test]
+    fn exact_number_patterns_should_match_same_kind() {
+        let third = crate::bignum::Rational::parse("1/3").unwrap();
+        let big = crate::bignum::BigInt::parse("123456789012345678901234567890").unwrap();
+
+        assert!( matches!( m(&Pat::Rational(third.clone()), &RuntimeData::Rational(third)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&Pat::BigInt(big.clone()), &RuntimeData::BigInt(big)), MatchResult::Env(_) ) );
+        assert!( matches!( m(&Pat::BigInt(crate::bignum::BigInt::from_i64(1)), &RuntimeData::Int(1)), MatchResult::NoMatch ) );
+    }
+}
@@ -4,12 +4,70 @@ mod parsing;
 mod evaling;
 mod runtime;
 mod compiling;
+mod natives;
+mod bignum;
+mod resolving;
+mod typing;
+#[cfg(test)]
+mod temp_dir;
+
+// NOTE:  A mint.toml that can't be read or parsed ends mint the same way an error in the file being run does.
+fn discover(dir : &std::path::Path) -> resolving::resolver::Resolver {
+    match resolving::resolver::Resolver::discover(dir) {
+        Ok(resolver) => resolver,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+    }
+}
+
+// NOTE:  Tokenize and parse errors are printed instead of ending the repl.
+fn read(input : &str) -> Option<ast::Top> {
+    let tokens = match parsing::tokenizer::tokenize(input) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            println!("{}", e);
+            return None;
+        },
+    };
+    match parsing::parser::parse(&tokens) {
+        Ok(top) => Some(top),
+        Err(e) => {
+            println!("parse failed: {:?}", e);
+            None
+        },
+    }
+}
 
 fn main() {
     use std::io::{stdout, stdin, Write};
 
     let mut c = evaling::data::Context::new();
 
+    // NOTE:  mint --check type checks everything before it runs and mint --release leaves out contracts.
+    let (flags, args) : (Vec<String>, Vec<String>) = std::env::args().skip(1).partition(|a| a.starts_with("--"));
+    c.check_types = flags.iter().any(|f| f == "--check");
+    c.contracts = !flags.iter().any(|f| f == "--release");
+
+    // NOTE:  mint path/to/file.mint runs the file instead of starting the repl.
+    if let Some(path) = args.first() {
+        let dir = std::path::Path::new(&path).parent().map(|d| d.to_path_buf()).unwrap_or_default();
+        c.resolver = discover(&dir);
+        // NOTE:  Errors are printed with Display so that runtime failures show their traceback.
+        match evaling::module::run_file(path, &mut c) {
+            Ok(Some(s)) => println!("{}", s),
+            Ok(None) => { },
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
+
+    c.resolver = discover(&std::env::current_dir().unwrap());
+
     loop {
         print!("> ");
         stdout().flush().unwrap();
@@ -18,12 +76,29 @@ fn main() {
         // TODO if it parses with no semi colon then it's some meaningful expr
         stdin().read_line(&mut input).unwrap();
 
-        let tokens = parsing::tokenizer::tokenize(&input).unwrap();
-        let top = parsing::parser::parse(&tokens).unwrap();
-        let s = evaling::evaler::eval(top, &mut c).unwrap();
+        // NOTE:  :type E shows the inferred type of E without running it.
+        if let Some(source) = input.trim().strip_prefix(":type ") {
+            if let Some(e) = read(source).and_then(|top| top.expr) {
+                match typing::infer::infer_expr(&e, &c.types, &c.natives) {
+                    Ok(t) => println!("{}", t),
+                    Err(error) => {
+                        let position = error.position();
+                        println!("{}", compiling::trace::SourceMap::new(None, source).locate(Box::new(error), position));
+                    },
+                }
+            }
+            continue;
+        }
 
-        if let Some(s) = s {
-            println!("{}", s);
+        let top = match read(&input) {
+            Some(top) => top,
+            None => { continue; },
+        };
+        c.source = compiling::trace::SourceMap::new(None, &input);
+        match evaling::evaler::eval(top, &mut c) {
+            Ok(Some(s)) => println!("{}", s),
+            Ok(None) => { },
+            Err(e) => println!("{}", e),
         }
     } 
 }
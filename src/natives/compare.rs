@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::cmp::Ordering;
+
+use crate::runtime::*;
+use crate::compiling::error::DynamicError;
+
+use super::*;
+
+pub fn register(table : &mut HashMap<String, NativeFn>) {
+    table.insert("==".into(), eq);
+    table.insert("compare".into(), compare);
+}
+
+fn eq(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> Result<RuntimeData, DynamicError> {
+    arity("==", 2, &params)?;
+    Ok(boolean(deep_eq(heap, &params[0], &params[1])))
+}
+
+// NOTE:  Returns the symbol lt, eq, or gt using the ordering from runtime::deep_cmp.
+fn compare(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> Result<RuntimeData, DynamicError> {
+    arity("compare", 2, &params)?;
+    match deep_cmp(heap, &params[0], &params[1]) {
+        Some(Ordering::Less) => Ok(symbol(heap, "lt")),
+        Some(Ordering::Equal) => Ok(symbol(heap, "eq")),
+        Some(Ordering::Greater) => Ok(symbol(heap, "gt")),
+        None => Err(DynamicError::CannotFindHeapAddress),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compare_should_return_ordering_symbol() {
+        let natives = Natives::new();
+        let mut heap = Heap::new();
+
+        let output = natives.call("compare", &mut heap, vec![RuntimeData::Number(1.0), RuntimeData::String("a".into())]).unwrap();
+
+        assert_eq!( output, symbol(&mut heap, "lt") );
+    }
+
+    #[test]
+    fn eq_should_compare_through_addresses() {
+        let natives = Natives::new();
+        let mut heap = Heap::new();
+        let address = heap.insert_new(RuntimeData::Tuple(vec![RuntimeData::Number(1.0)]));
+
+        let output = natives.call("==", &mut heap, vec![RuntimeData::Address(address), RuntimeData::Tuple(vec![RuntimeData::Number(1.0)])]).unwrap();
+
+        assert_eq!( output, RuntimeData::Symbol(SymbolTable::TRUE) );
+    }
+
+    #[test]
+    fn eq_should_reject_wrong_arity() {
+        let natives = Natives::new();
+        let mut heap = Heap::new();
+
+        let output = natives.call("==", &mut heap, vec![RuntimeData::Number(1.0)]);
+
+        assert!( matches!( output, Err(DynamicError::WrongArity { .. }) ) );
+    }
+}
@@ -0,0 +1,19 @@
+
+use std::collections::HashMap;
+
+use crate::runtime::*;
+use crate::compiling::error::DynamicError;
+
+use super::*;
+
+pub fn register(table : &mut HashMap<String, NativeFn>) {
+    table.insert("raise".into(), raise);
+}
+
+// NOTE:  Unwinds to the closest catch clause that matches the value (see instr::try_catch).
+fn raise(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> Result<RuntimeData, DynamicError> {
+    arity("raise", 1, &params)?;
+    let raised = deref(heap, &params[0])?.clone();
+    let shown = show(heap, &raised, true)?;
+    Err(DynamicError::Uncaught(raised, shown))
+}
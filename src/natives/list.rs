@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::cmp::Ordering;
+
+use crate::runtime::*;
+use crate::compiling::error::DynamicError;
+
+use super::*;
+
+type R = Result<RuntimeData, DynamicError>;
+
+pub fn register(table : &mut HashMap<String, NativeFn>) {
+    table.insert("length".into(), length);
+    table.insert("is_empty".into(), is_empty);
+    table.insert("map".into(), map);
+    table.insert("filter".into(), filter);
+    table.insert("fold_left".into(), fold_left);
+    table.insert("fold_right".into(), fold_right);
+    table.insert("reverse".into(), reverse);
+    table.insert("append".into(), append);
+    table.insert("concat".into(), concat);
+    table.insert("zip".into(), zip);
+    table.insert("range".into(), range);
+    table.insert("nth".into(), nth);
+    table.insert("take".into(), take);
+    table.insert("drop".into(), drop);
+    table.insert("sort_by".into(), sort_by);
+    table.insert("any".into(), any);
+    table.insert("all".into(), all);
+    table.insert("find".into(), find);
+}
+
+fn length(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("length", 1, &params)?;
+    Ok(RuntimeData::Int(list(heap, &params[0])?.len() as i64))
+}
+
+fn is_empty(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("is_empty", 1, &params)?;
+    Ok(boolean(list(heap, &params[0])?.is_empty()))
+}
+
+// NOTE:  map(List, F)
+fn map(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("map", 2, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    let mut ret = vec![];
+    for x in xs {
+        ret.push(natives.apply(heap, &params[1], vec![x])?);
+    }
+    Ok(RuntimeData::List(ret))
+}
+
+// NOTE:  filter(List, Predicate)
+fn filter(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("filter", 2, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    let mut ret = vec![];
+    for x in xs {
+        let keep = natives.apply(heap, &params[1], vec![x.clone()])?;
+        if truthy(heap, &keep)? {
+            ret.push(x);
+        }
+    }
+    Ok(RuntimeData::List(ret))
+}
+
+// NOTE:  fold_left(List, Init, F) where F is called as F(Acc, Item) from the front of the list.
+fn fold_left(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("fold_left", 3, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    let mut acc = params[1].clone();
+    for x in xs {
+        acc = natives.apply(heap, &params[2], vec![acc, x])?;
+    }
+    Ok(acc)
+}
+
+// NOTE:  fold_right(List, Init, F) where F is called as F(Item, Acc) from the back of the list.
+fn fold_right(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("fold_right", 3, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    let mut acc = params[1].clone();
+    for x in xs.into_iter().rev() {
+        acc = natives.apply(heap, &params[2], vec![x, acc])?;
+    }
+    Ok(acc)
+}
+
+fn reverse(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("reverse", 1, &params)?;
+    Ok(RuntimeData::List(list(heap, &params[0])?.iter().rev().cloned().collect()))
+}
+
+fn append(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("append", 2, &params)?;
+    let mut ret = list(heap, &params[0])?.clone();
+    ret.extend(list(heap, &params[1])?.iter().cloned());
+    Ok(RuntimeData::List(ret))
+}
+
+// NOTE:  concat(ListOfLists) flattens one level, while concat(ListOfStrings) and concat(String, String, ...) join
+// strings together.
+fn concat(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    if params.len() != 1 {
+        let mut ret = String::new();
+        for x in params.iter() {
+            ret.push_str(string(heap, x)?);
+        }
+        return Ok(RuntimeData::String(ret));
+    }
+
+    match deref(heap, &params[0])? {
+        RuntimeData::String(x) => Ok(RuntimeData::String(x.clone())),
+        RuntimeData::List(xs) => {
+            // NOTE:  Every element is checked so that a mixed list is reported as such instead of failing on
+            // whichever element happens to disagree with the first one.
+            let items = xs.iter().map(|x| deref(heap, x)).collect::<Result<Vec<_>, _>>()?;
+            if items.len() > 0 && items.iter().all(|x| matches!(x, RuntimeData::String(_))) {
+                let mut ret = String::new();
+                for x in items {
+                    if let RuntimeData::String(x) = x {
+                        ret.push_str(x);
+                    }
+                }
+                Ok(RuntimeData::String(ret))
+            }
+            else if items.iter().all(|x| matches!(x, RuntimeData::List(_))) {
+                let mut ret = vec![];
+                for xs in items {
+                    if let RuntimeData::List(xs) = xs {
+                        ret.extend(xs.iter().cloned());
+                    }
+                }
+                Ok(RuntimeData::List(ret))
+            }
+            else {
+                let mut kinds : Vec<&str> = vec![];
+                for x in items {
+                    if !kinds.contains(&kind(x)) {
+                        kinds.push(kind(x));
+                    }
+                }
+                Err(DynamicError::TypeMismatch { expected: "List of Strings or List of Lists".into(), observed: format!("List of {}", kinds.join(" and ")) })
+            }
+        },
+        data => Err(mismatch("List or String", data)),
+    }
+}
+
+// NOTE:  zip(A, B) returns a list of tuples and stops at the end of the shorter list.
+fn zip(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("zip", 2, &params)?;
+    let xs = list(heap, &params[0])?;
+    let ys = list(heap, &params[1])?;
+    Ok(RuntimeData::List(std::iter::zip(xs, ys).map(|(x, y)| RuntimeData::Tuple(vec![x.clone(), y.clone()])).collect()))
+}
+
+const MAX_RANGE_LENGTH : i128 = 1 << 24;
+
+// NOTE:  range(Start, End) counts by one from Start up to, but not including, End.  The length is worked out ahead of
+// time so that bounds too far apart (or too large to count by one) are reported instead of running out of memory.
+fn range(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("range", 2, &params)?;
+    let invalid = || DynamicError::InvalidRange { start: show(heap, &params[0], true).unwrap_or_default(), end: show(heap, &params[1], true).unwrap_or_default() };
+    if let (Ok(start), Ok(end)) = (int(heap, &params[0]), int(heap, &params[1])) {
+        let length = (end as i128 - start as i128).max(0);
+        if length > MAX_RANGE_LENGTH {
+            return Err(invalid());
+        }
+        return Ok(RuntimeData::List((0..length as i64).map(|i| RuntimeData::Int(start + i)).collect()));
+    }
+    let start = number(heap, &params[0])?;
+    let end = number(heap, &params[1])?;
+    if !start.is_finite() || !end.is_finite() || (end - start).ceil() > MAX_RANGE_LENGTH as f64 {
+        return Err(invalid());
+    }
+    let length = (end - start).ceil().max(0.0) as i64;
+    Ok(RuntimeData::List((0..length).map(|i| RuntimeData::Number(start + i as f64)).collect()))
+}
+
+// NOTE:  nth(List, Index) with a zero based index.
+fn nth(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("nth", 2, &params)?;
+    let xs = list(heap, &params[0])?;
+    let i = index(heap, &params[1])?;
+    match xs.get(i) {
+        Some(x) => Ok(x.clone()),
+        None => Err(DynamicError::IndexOutOfRange { index: i, length: xs.len() }),
+    }
+}
+
+fn take(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("take", 2, &params)?;
+    let xs = list(heap, &params[0])?;
+    let n = index(heap, &params[1])?;
+    Ok(RuntimeData::List(xs.iter().take(n).cloned().collect()))
+}
+
+fn drop(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("drop", 2, &params)?;
+    let xs = list(heap, &params[0])?;
+    let n = index(heap, &params[1])?;
+    Ok(RuntimeData::List(xs.iter().skip(n).cloned().collect()))
+}
+
+// NOTE:  sort_by(List, F) where F(A, B) returns lt, eq, or gt (see compare).  The sort is stable.  F can be any mint
+// function, so the merge sort has to finish even when its results aren't a total order.
+fn sort_by(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    fn merge_sort(xs : Vec<RuntimeData>, less : &mut dyn FnMut(&RuntimeData, &RuntimeData) -> Result<bool, DynamicError>) -> Result<Vec<RuntimeData>, DynamicError> {
+        if xs.len() <= 1 {
+            return Ok(xs);
+        }
+        let mut left = xs;
+        let right = left.split_off(left.len() / 2);
+        let left = merge_sort(left, less)?;
+        let right = merge_sort(right, less)?;
+
+        let mut ret = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+        while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+            // NOTE:  The left item goes first unless the right one is less, which keeps equal items in order.
+            if less(r, l)? {
+                ret.extend(right.next());
+            }
+            else {
+                ret.extend(left.next());
+            }
+        }
+        ret.extend(left);
+        ret.extend(right);
+        Ok(ret)
+    }
+
+    arity("sort_by", 2, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    let xs = merge_sort(xs, &mut |a : &RuntimeData, b : &RuntimeData| {
+        let result = natives.apply(heap, &params[1], vec![a.clone(), b.clone()])?;
+        Ok(ordering(heap, &result)? == Ordering::Less)
+    })?;
+    Ok(RuntimeData::List(xs))
+}
+
+fn ordering(heap : &Heap, data : &RuntimeData) -> Result<Ordering, DynamicError> {
+    match symbol_name(heap, data) {
+        Ok("lt") => Ok(Ordering::Less),
+        Ok("eq") => Ok(Ordering::Equal),
+        Ok("gt") => Ok(Ordering::Greater),
+        _ => Err(mismatch("Symbol lt, eq, or gt", deref(heap, data)?)),
+    }
+}
+
+fn any(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("any", 2, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    for x in xs {
+        let result = natives.apply(heap, &params[1], vec![x])?;
+        if truthy(heap, &result)? {
+            return Ok(boolean(true));
+        }
+    }
+    Ok(boolean(false))
+}
+
+fn all(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("all", 2, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    for x in xs {
+        let result = natives.apply(heap, &params[1], vec![x])?;
+        if !truthy(heap, &result)? {
+            return Ok(boolean(false));
+        }
+    }
+    Ok(boolean(true))
+}
+
+// NOTE:  find(List, Predicate) returns {some, Item} for the first matching item or the symbol none.
+fn find(natives : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("find", 2, &params)?;
+    let xs = list(heap, &params[0])?.clone();
+    for x in xs {
+        let result = natives.apply(heap, &params[1], vec![x.clone()])?;
+        if truthy(heap, &result)? {
+            return Ok(RuntimeData::Tuple(vec![symbol(heap, "some"), x]));
+        }
+    }
+    Ok(symbol(heap, "none"))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn numbers(ns : &[f64]) -> RuntimeData {
+        RuntimeData::List(ns.iter().map(|n| RuntimeData::Number(*n)).collect())
+    }
+
+    fn call(name : &str, params : Vec<RuntimeData>) -> R {
+        Natives::new().call(name, &mut Heap::new(), params)
+    }
+
+    fn call_with(heap : &mut Heap, name : &str, params : Vec<RuntimeData>) -> R {
+        Natives::new().call(name, heap, params)
+    }
+
+    #[test]
+    fn sort_by_should_sort_with_compare() {
+        let mut heap = Heap::new();
+        let compare = symbol(&mut heap, "compare");
+
+        let output = call_with(&mut heap, "sort_by", vec![numbers(&[3.0, 1.0, 2.0]), compare]).unwrap();
+
+        assert_eq!( output, numbers(&[1.0, 2.0, 3.0]) );
+    }
+
+    #[test]
+    fn sort_by_should_report_bad_comparison_result() {
+        let mut heap = Heap::new();
+        let eq = symbol(&mut heap, "==");
+
+        let output = call_with(&mut heap, "sort_by", vec![numbers(&[3.0, 1.0, 2.0]), eq]);
+
+        assert!( matches!( output, Err(DynamicError::TypeMismatch { .. }) ) );
+    }
+
+    #[test]
+    fn range_should_count_from_number_start() {
+        let output = call("range", vec![RuntimeData::Number(0.5), RuntimeData::Int(3)]).unwrap();
+
+        assert_eq!( output, numbers(&[0.5, 1.5, 2.5]) );
+    }
+
+    #[test]
+    fn range_should_reject_ranges_too_large_to_count() {
+        let output = call("range", vec![RuntimeData::Int(0), RuntimeData::Int(4_000_000_000_000)]);
+        assert!( matches!( output, Err(DynamicError::InvalidRange { .. }) ) );
+
+        let output = call("range", vec![RuntimeData::Number(1e16), RuntimeData::Number(1e17)]);
+        assert!( matches!( output, Err(DynamicError::InvalidRange { .. }) ) );
+
+        let output = call("range", vec![RuntimeData::Int(0), RuntimeData::Number(f64::INFINITY)]);
+        assert!( matches!( output, Err(DynamicError::InvalidRange { .. }) ) );
+    }
+
+    #[test]
+    fn nth_should_reject_out_of_range_index() {
+        let output = call("nth", vec![numbers(&[1.0]), RuntimeData::Number(1.0)]);
+
+        assert!( matches!( output, Err(DynamicError::IndexOutOfRange { index: 1, length: 1 }) ) );
+    }
+
+    #[test]
+    fn nth_should_reject_fractional_index() {
+        let output = call("nth", vec![numbers(&[1.0]), RuntimeData::Number(0.5)]);
+
+        assert!( matches!( output, Err(DynamicError::InvalidIndex(_)) ) );
+    }
+
+    #[test]
+    fn find_should_return_first_match() {
+        let mut heap = Heap::new();
+        let input = RuntimeData::List(vec![numbers(&[1.0]), numbers(&[]), numbers(&[])]);
+        let is_empty = symbol(&mut heap, "is_empty");
+
+        let output = call_with(&mut heap, "find", vec![input, is_empty]).unwrap();
+
+        assert_eq!( output, RuntimeData::Tuple(vec![symbol(&mut heap, "some"), numbers(&[])]) );
+    }
+
+    #[test]
+    fn concat_should_flatten_list_of_lists() {
+        let input = RuntimeData::List(vec![numbers(&[1.0]), numbers(&[2.0, 3.0])]);
+
+        let output = call("concat", vec![input]).unwrap();
+
+        assert_eq!( output, numbers(&[1.0, 2.0, 3.0]) );
+    }
+
+    #[test]
+    fn concat_should_reject_mixed_list() {
+        let input = RuntimeData::List(vec![RuntimeData::String("a".into()), numbers(&[2.0])]);
+
+        let output = call("concat", vec![input]);
+
+        assert!( matches!( output, Err(DynamicError::TypeMismatch { observed, .. }) if observed == "List of String and List" ) );
+    }
+}
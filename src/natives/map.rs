@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::runtime::*;
+use crate::compiling::error::DynamicError;
+
+use super::*;
+
+type R = Result<RuntimeData, DynamicError>;
+
+// NOTE:  Maps are persistent, so every function that changes a map returns a new map and leaves the original alone.
+pub fn register(table : &mut HashMap<String, NativeFn>) {
+    table.insert("get".into(), get);
+    table.insert("put".into(), put);
+    table.insert("remove".into(), remove);
+    table.insert("keys".into(), keys);
+    table.insert("values".into(), values);
+    table.insert("merge".into(), merge);
+}
+
+// NOTE:  get(Map, Key) returns {some, Value} or the symbol none, while get(Map, Key, Default) returns Value or Default.
+fn get(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    if params.len() != 2 {
+        arity("get", 3, &params)?;
+    }
+    let entries = map(heap, &params[0])?;
+    let found = match map_search(heap, entries, &params[1]).ok_or(DynamicError::CannotFindHeapAddress)? {
+        Ok(i) => Some(entries[i].1.clone()),
+        Err(_) => None,
+    };
+    match (found, params.len()) {
+        (Some(v), 2) => Ok(RuntimeData::Tuple(vec![symbol(heap, "some"), v])),
+        (None, 2) => Ok(symbol(heap, "none")),
+        (Some(v), _) => Ok(v),
+        (None, _) => Ok(params[2].clone()),
+    }
+}
+
+// NOTE:  put(Map, Key, Value)
+fn put(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("put", 3, &params)?;
+    let mut entries = map(heap, &params[0])?.clone();
+    map_insert(heap, &mut entries, params[1].clone(), params[2].clone()).ok_or(DynamicError::CannotFindHeapAddress)?;
+    Ok(RuntimeData::Map(entries))
+}
+
+// NOTE:  remove(Map, Key)
+fn remove(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("remove", 2, &params)?;
+    let mut entries = map(heap, &params[0])?.clone();
+    match map_search(heap, &entries, &params[1]).ok_or(DynamicError::CannotFindHeapAddress)? {
+        Ok(i) => { entries.remove(i); },
+        Err(_) => { },
+    }
+    Ok(RuntimeData::Map(entries))
+}
+
+fn keys(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("keys", 1, &params)?;
+    Ok(RuntimeData::List(map(heap, &params[0])?.iter().map(|(k, _)| k.clone()).collect()))
+}
+
+fn values(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("values", 1, &params)?;
+    Ok(RuntimeData::List(map(heap, &params[0])?.iter().map(|(_, v)| v.clone()).collect()))
+}
+
+// NOTE:  merge(A, B) where the values in B win when both maps have the same key.
+fn merge(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("merge", 2, &params)?;
+    let mut entries = map(heap, &params[0])?.clone();
+    for (k, v) in map(heap, &params[1])?.clone() {
+        map_insert(heap, &mut entries, k, v).ok_or(DynamicError::CannotFindHeapAddress)?;
+    }
+    Ok(RuntimeData::Map(entries))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn n(x : f64) -> RuntimeData {
+        RuntimeData::Number(x)
+    }
+
+    #[test]
+    fn put_should_not_change_original_map() {
+        let mut heap = Heap::new();
+        let original = RuntimeData::Map(vec![(n(1.0), n(2.0))]);
+
+        let output = Natives::new().call("put", &mut heap, vec![original.clone(), n(1.0), n(3.0)]).unwrap();
+
+        assert_eq!( output, RuntimeData::Map(vec![(n(1.0), n(3.0))]) );
+        assert_eq!( original, RuntimeData::Map(vec![(n(1.0), n(2.0))]) );
+    }
+
+    #[test]
+    fn get_should_find_structurally_equal_key() {
+        let mut heap = Heap::new();
+        let key = heap.insert_new(RuntimeData::List(vec![n(1.0)]));
+        let input = RuntimeData::Map(vec![(RuntimeData::Address(key), n(2.0))]);
+
+        let output = Natives::new().call("get", &mut heap, vec![input, RuntimeData::List(vec![n(1.0)]), n(0.0)]).unwrap();
+
+        assert_eq!( output, n(2.0) );
+    }
+
+    #[test]
+    fn merge_should_prefer_second_map() {
+        let mut heap = Heap::new();
+        let a = RuntimeData::Map(vec![(n(1.0), n(1.0)), (n(2.0), n(2.0))]);
+        let b = RuntimeData::Map(vec![(n(2.0), n(3.0))]);
+
+        let output = Natives::new().call("merge", &mut heap, vec![a, b]).unwrap();
+
+        assert_eq!( output, RuntimeData::Map(vec![(n(1.0), n(1.0)), (n(2.0), n(3.0))]) );
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::runtime::*;
+use crate::compiling::error::DynamicError;
+use crate::compiling::lambdas::Lambdas;
+
+mod compare;
+mod list;
+mod string;
+mod map;
+mod number;
+mod error;
+
+pub type NativeFn = fn(&Natives, &mut Heap, Vec<RuntimeData>) -> Result<RuntimeData, DynamicError>;
+
+// NOTE:  Natives are called by symbol, ie compare(A, B) calls the native registered as "compare".  The compiled
+// lambdas are kept alongside them so that natives like map can call a lambda they were passed.
+#[derive(Clone)]
+pub struct Natives {
+    table : Rc<HashMap<String, NativeFn>>,
+    lambdas : Lambdas,
+}
+
+impl Natives {
+    pub fn new() -> Self {
+        let mut table = HashMap::new();
+        compare::register(&mut table);
+        list::register(&mut table);
+        string::register(&mut table);
+        map::register(&mut table);
+        number::register(&mut table);
+        error::register(&mut table);
+        Natives { table: Rc::new(table), lambdas: Lambdas::default() }
+    }
+
+    pub fn lambdas(&self) -> &Lambdas {
+        &self.lambdas
+    }
+
+    pub fn contains(&self, name : &str) -> bool {
+        self.table.contains_key(name)
+    }
+
+    pub fn call(&self, name : &str, heap : &mut Heap, params : Vec<RuntimeData>) -> Result<RuntimeData, DynamicError> {
+        match self.table.get(name) {
+            Some(f) => f(self, heap, params),
+            None => Err(DynamicError::FunctionNotDefined(name.into())),
+        }
+    }
+
+    // NOTE:  Calls a function value, ie the symbol map passed as a parameter calls the map native and a lambda runs
+    // its body.
+    pub fn apply(&self, heap : &mut Heap, func : &RuntimeData, params : Vec<RuntimeData>) -> Result<RuntimeData, DynamicError> {
+        let name = match deref(heap, func)? {
+            RuntimeData::Symbol(id) => heap.symbols().name(*id).to_string(),
+            RuntimeData::Function(f) => {
+                let f = *f;
+                return self.lambdas.call(heap, f, params).map_err(|e| match e.downcast::<DynamicError>() {
+                    Ok(e) => *e,
+                    Err(e) => DynamicError::Nested(e),
+                });
+            },
+            data => { return Err(DynamicError::NotCallable(kind(data).into())); },
+        };
+        self.call(&name, heap, params)
+    }
+}
+
+pub fn arity(name : &str, expected : usize, params : &Vec<RuntimeData>) -> Result<(), DynamicError> {
+    if params.len() == expected {
+        Ok(())
+    }
+    else {
+        Err(DynamicError::WrongArity { name: name.into(), expected, observed: params.len() })
+    }
+}
+
+pub fn deref<'a>(heap : &'a Heap, data : &'a RuntimeData) -> Result<&'a RuntimeData, DynamicError> {
+    match data {
+        RuntimeData::Address(address) => deref(heap, heap.get(*address).ok_or(DynamicError::CannotFindHeapAddress)?),
+        data => Ok(data),
+    }
+}
+
+pub fn kind(data : &RuntimeData) -> &'static str {
+    match data {
+        RuntimeData::Address(_) => "Address",
+        RuntimeData::Function(_) => "Function",
+        RuntimeData::Number(_) => "Number",
+        RuntimeData::Int(_) => "Int",
+        RuntimeData::BigInt(_) => "BigInt",
+        RuntimeData::Rational(_) => "Rational",
+        RuntimeData::String(_) => "String",
+        RuntimeData::Char(_) => "Char",
+        RuntimeData::Symbol(_) => "Symbol",
+        RuntimeData::List(_) => "List",
+        RuntimeData::Tuple(_) => "Tuple",
+        RuntimeData::Map(_) => "Map",
+    }
+}
+
+pub fn mismatch(expected : &str, observed : &RuntimeData) -> DynamicError {
+    DynamicError::TypeMismatch { expected: expected.into(), observed: kind(observed).into() }
+}
+
+pub fn list<'a>(heap : &'a Heap, data : &'a RuntimeData) -> Result<&'a Vec<RuntimeData>, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::List(xs) => Ok(xs),
+        data => Err(mismatch("List", data)),
+    }
+}
+
+pub fn map<'a>(heap : &'a Heap, data : &'a RuntimeData) -> Result<&'a Vec<(RuntimeData, RuntimeData)>, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Map(m) => Ok(m),
+        data => Err(mismatch("Map", data)),
+    }
+}
+
+pub fn string<'a>(heap : &'a Heap, data : &'a RuntimeData) -> Result<&'a String, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::String(x) => Ok(x),
+        data => Err(mismatch("String", data)),
+    }
+}
+
+pub fn char(heap : &Heap, data : &RuntimeData) -> Result<char, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Char(x) => Ok(*x),
+        data => Err(mismatch("Char", data)),
+    }
+}
+
+pub fn symbol_name<'a>(heap : &'a Heap, data : &'a RuntimeData) -> Result<&'a str, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Symbol(id) => Ok(heap.symbols().name(*id)),
+        data => Err(mismatch("Symbol", data)),
+    }
+}
+
+// NOTE:  Other numbers are converted so that functions which only work with f64 also accept them.  BigInts and
+// Rationals are rounded to the nearest f64, so this is only for places where the result is a Number anyway.
+pub fn number(heap : &Heap, data : &RuntimeData) -> Result<f64, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Number(x) => Ok(*x),
+        RuntimeData::Int(x) => Ok(*x as f64),
+        RuntimeData::BigInt(x) => Ok(x.to_f64()),
+        RuntimeData::Rational(x) => Ok(x.to_f64()),
+        data => Err(mismatch("Number", data)),
+    }
+}
+
+pub fn int(heap : &Heap, data : &RuntimeData) -> Result<i64, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Int(x) => Ok(*x),
+        data => Err(mismatch("Int", data)),
+    }
+}
+
+pub fn index(heap : &Heap, data : &RuntimeData) -> Result<usize, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Int(x) if *x >= 0 => Ok(*x as usize),
+        RuntimeData::Int(x) => Err(DynamicError::InvalidIndex(*x as f64)),
+        RuntimeData::Number(x) if *x >= 0.0 && x.fract() == 0.0 => Ok(*x as usize),
+        RuntimeData::Number(x) => Err(DynamicError::InvalidIndex(*x)),
+        data => Err(mismatch("Int", data)),
+    }
+}
+
+pub fn truthy(heap : &Heap, data : &RuntimeData) -> Result<bool, DynamicError> {
+    match deref(heap, data)? {
+        RuntimeData::Symbol(SymbolTable::TRUE) => Ok(true),
+        RuntimeData::Symbol(SymbolTable::FALSE) => Ok(false),
+        data => Err(mismatch("Symbol true or false", data)),
+    }
+}
+
+// NOTE:  Converts any data into a string.  Strings and chars are only quoted when they are inside of other data.
+pub fn show(heap : &Heap, data : &RuntimeData, nested : bool) -> Result<String, DynamicError> {
+    fn all(heap : &Heap, xs : &[RuntimeData]) -> Result<String, DynamicError> {
+        Ok(xs.iter().map(|x| show(heap, x, true)).collect::<Result<Vec<_>, _>>()?.join(", "))
+    }
+    Ok(match deref(heap, data)? {
+        RuntimeData::String(x) if nested => format!("{:?}", x),
+        RuntimeData::String(x) => x.clone(),
+        RuntimeData::Char(x) if nested => format!("{:?}", x),
+        RuntimeData::Char(x) => x.to_string(),
+        RuntimeData::Number(x) => format!("{:?}", x),
+        RuntimeData::Int(x) => x.to_string(),
+        RuntimeData::BigInt(x) => format!("{}n", x),
+        RuntimeData::Rational(x) => format!("{}r", x),
+        RuntimeData::Symbol(x) => heap.symbols().name(*x).into(),
+        RuntimeData::List(xs) => format!("[{}]", all(heap, xs)?),
+        RuntimeData::Tuple(xs) => format!("{{{}}}", all(heap, xs)?),
+        RuntimeData::Map(kvps) => format!("%{{{}}}", kvps.iter()
+                                                        .map(|(k, v)| Ok(format!("{} => {}", show(heap, k, true)?, show(heap, v, true)?)))
+                                                        .collect::<Result<Vec<_>, DynamicError>>()?
+                                                        .join(", ")),
+        RuntimeData::Function(_) => "<function>".into(),
+        RuntimeData::Address(_) => unreachable!("deref follows addresses"),
+    })
+}
+
+pub fn boolean(b : bool) -> RuntimeData {
+    if b {
+        RuntimeData::Symbol(SymbolTable::TRUE)
+    }
+    else {
+        RuntimeData::Symbol(SymbolTable::FALSE)
+    }
+}
+
+pub fn symbol(heap : &mut Heap, name : &str) -> RuntimeData {
+    RuntimeData::Symbol(heap.symbols_mut().intern(name))
+}
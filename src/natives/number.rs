@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::runtime::*;
+use crate::bignum::{BigInt, Rational};
+use crate::compiling::error::DynamicError;
+
+use super::*;
+
+type R = Result<RuntimeData, DynamicError>;
+
+// NOTE:  Arithmetic on two Ints is overflow checked and stays an Int.  Otherwise the result is the widest kind of the
+// two sides where Int < BigInt < Rational < Number.  BigInts and Rationals are exact and only Numbers (f64) round.
+pub fn register(table : &mut HashMap<String, NativeFn>) {
+    table.insert("add".into(), add);
+    table.insert("sub".into(), sub);
+    table.insert("mul".into(), mul);
+    table.insert("div".into(), div);
+    table.insert("rem".into(), rem);
+    table.insert("to_int".into(), to_int);
+    table.insert("to_float".into(), to_float);
+    table.insert("to_bigint".into(), to_bigint);
+    table.insert("to_rational".into(), to_rational);
+    table.insert("numerator".into(), numerator);
+    table.insert("denominator".into(), denominator);
+}
+
+struct Ops {
+    int : fn(i64, i64) -> Result<i64, DynamicError>,
+    big : fn(&BigInt, &BigInt) -> Result<BigInt, DynamicError>,
+    ratio : fn(&Rational, &Rational) -> Result<Rational, DynamicError>,
+    float : fn(f64, f64) -> f64,
+}
+
+fn exact(data : &RuntimeData) -> Option<Rational> {
+    match data {
+        RuntimeData::Int(x) => Some(Rational::from_bigint(BigInt::from_i64(*x))),
+        RuntimeData::BigInt(x) => Some(Rational::from_bigint(x.clone())),
+        RuntimeData::Rational(x) => Some(x.clone()),
+        _ => None,
+    }
+}
+
+fn big(data : &RuntimeData) -> Option<BigInt> {
+    match data {
+        RuntimeData::Int(x) => Some(BigInt::from_i64(*x)),
+        RuntimeData::BigInt(x) => Some(x.clone()),
+        _ => None,
+    }
+}
+
+fn arithmetic(name : &str, heap : &Heap, params : &Vec<RuntimeData>, ops : Ops) -> R {
+    arity(name, 2, params)?;
+    let a = deref(heap, &params[0])?;
+    let b = deref(heap, &params[1])?;
+    match (a, b) {
+        (RuntimeData::Int(a), RuntimeData::Int(b)) => Ok(RuntimeData::Int((ops.int)(*a, *b)?)),
+        (RuntimeData::Number(_), _) | (_, RuntimeData::Number(_)) => Ok(RuntimeData::Number((ops.float)(number(heap, a)?, number(heap, b)?))),
+        (RuntimeData::Rational(_), _) | (_, RuntimeData::Rational(_)) => match (exact(a), exact(b)) {
+            (Some(a), Some(b)) => Ok(RuntimeData::Rational((ops.ratio)(&a, &b)?)),
+            _ => Err(mismatch("Number", if exact(a).is_none() { a } else { b })),
+        },
+        _ => match (big(a), big(b)) {
+            (Some(a), Some(b)) => Ok(RuntimeData::BigInt((ops.big)(&a, &b)?)),
+            _ => Err(mismatch("Number", if big(a).is_none() { a } else { b })),
+        },
+    }
+}
+
+fn overflow(name : &str) -> DynamicError {
+    DynamicError::IntegerOverflow(name.into())
+}
+
+fn add(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arithmetic("add", heap, &params, Ops { int: |a, b| a.checked_add(b).ok_or(overflow("add"))
+                                         , big: |a, b| Ok(a + b)
+                                         , ratio: |a, b| Ok(a + b)
+                                         , float: |a, b| a + b
+                                         })
+}
+
+fn sub(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arithmetic("sub", heap, &params, Ops { int: |a, b| a.checked_sub(b).ok_or(overflow("sub"))
+                                         , big: |a, b| Ok(a - b)
+                                         , ratio: |a, b| Ok(a - b)
+                                         , float: |a, b| a - b
+                                         })
+}
+
+fn mul(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arithmetic("mul", heap, &params, Ops { int: |a, b| a.checked_mul(b).ok_or(overflow("mul"))
+                                         , big: |a, b| Ok(a * b)
+                                         , ratio: |a, b| Ok(a * b)
+                                         , float: |a, b| a * b
+                                         })
+}
+
+// NOTE:  Int and BigInt division truncates toward zero while Rational division is exact.
+fn div(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arithmetic("div", heap, &params, Ops { int: |a, b| match b {
+                                               0 => Err(DynamicError::DivisionByZero),
+                                               _ => a.checked_div(b).ok_or(overflow("div")),
+                                           }
+                                         , big: |a, b| Ok(a.checked_div_rem(b).ok_or(DynamicError::DivisionByZero)?.0)
+                                         , ratio: |a, b| a.checked_div(b).ok_or(DynamicError::DivisionByZero)
+                                         , float: |a, b| a / b
+                                         })
+}
+
+// NOTE:  The result has the same sign as the dividend.
+fn rem(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arithmetic("rem", heap, &params, Ops { int: |a, b| match b {
+                                               0 => Err(DynamicError::DivisionByZero),
+                                               _ => a.checked_rem(b).ok_or(overflow("rem")),
+                                           }
+                                         , big: |a, b| Ok(a.checked_div_rem(b).ok_or(DynamicError::DivisionByZero)?.1)
+                                         , ratio: |a, b| a.checked_rem(b).ok_or(DynamicError::DivisionByZero)
+                                         , float: |a, b| a % b
+                                         })
+}
+
+// NOTE:  Everything besides Ints is truncated toward zero.
+fn to_int(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_int", 1, &params)?;
+    match deref(heap, &params[0])? {
+        RuntimeData::Int(x) => Ok(RuntimeData::Int(*x)),
+        RuntimeData::BigInt(x) => Ok(RuntimeData::Int(x.to_i64().ok_or(overflow("to_int"))?)),
+        RuntimeData::Rational(x) => Ok(RuntimeData::Int(x.trunc().to_i64().ok_or(overflow("to_int"))?)),
+        RuntimeData::Number(x) => {
+            let whole = x.trunc();
+            // NOTE:  NaN fails both comparisons.
+            if whole >= -9223372036854775808.0 && whole < 9223372036854775808.0 {
+                Ok(RuntimeData::Int(whole as i64))
+            }
+            else {
+                Err(DynamicError::CannotConvertToInt(*x))
+            }
+        },
+        data => Err(mismatch("Number", data)),
+    }
+}
+
+fn to_float(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_float", 1, &params)?;
+    Ok(RuntimeData::Number(number(heap, &params[0])?))
+}
+
+// NOTE:  Rationals and Numbers are truncated toward zero.
+fn to_bigint(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_bigint", 1, &params)?;
+    match deref(heap, &params[0])? {
+        RuntimeData::Number(x) => Ok(RuntimeData::BigInt(Rational::from_f64(*x).ok_or(DynamicError::CannotConvertToInt(*x))?.trunc())),
+        RuntimeData::Rational(x) => Ok(RuntimeData::BigInt(x.trunc())),
+        data => Ok(RuntimeData::BigInt(big(data).ok_or_else(|| mismatch("Number", data))?)),
+    }
+}
+
+// NOTE:  Numbers are converted exactly, so to_rational(0.1) is the (slightly larger than 1/10) value of the f64.
+fn to_rational(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_rational", 1, &params)?;
+    match deref(heap, &params[0])? {
+        RuntimeData::Number(x) => Ok(RuntimeData::Rational(Rational::from_f64(*x).ok_or(DynamicError::CannotConvertToRational(*x))?)),
+        data => Ok(RuntimeData::Rational(exact(data).ok_or_else(|| mismatch("Number", data))?)),
+    }
+}
+
+fn numerator(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("numerator", 1, &params)?;
+    let data = deref(heap, &params[0])?;
+    Ok(RuntimeData::BigInt(exact(data).ok_or_else(|| mismatch("Rational", data))?.numer().clone()))
+}
+
+fn denominator(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("denominator", 1, &params)?;
+    let data = deref(heap, &params[0])?;
+    Ok(RuntimeData::BigInt(exact(data).ok_or_else(|| mismatch("Rational", data))?.denom().clone()))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(name : &str, params : Vec<RuntimeData>) -> R {
+        Natives::new().call(name, &mut Heap::new(), params)
+    }
+
+    #[test]
+    fn int_arithmetic_should_stay_exact() {
+        let output = call("add", vec![RuntimeData::Int(9007199254740992), RuntimeData::Int(1)]).unwrap();
+
+        assert_eq!( output, RuntimeData::Int(9007199254740993) );
+    }
+
+    #[test]
+    fn mixed_arithmetic_should_produce_number() {
+        let output = call("mul", vec![RuntimeData::Int(3), RuntimeData::Number(0.5)]).unwrap();
+
+        assert_eq!( output, RuntimeData::Number(1.5) );
+    }
+
+    #[test]
+    fn int_arithmetic_should_report_overflow() {
+        let output = call("add", vec![RuntimeData::Int(i64::MAX), RuntimeData::Int(1)]);
+
+        assert!( matches!( output, Err(DynamicError::IntegerOverflow(x)) if x == "add" ) );
+    }
+
+    #[test]
+    fn int_division_should_truncate_and_reject_zero() {
+        assert_eq!( call("div", vec![RuntimeData::Int(-7), RuntimeData::Int(2)]).unwrap(), RuntimeData::Int(-3) );
+        assert_eq!( call("rem", vec![RuntimeData::Int(-7), RuntimeData::Int(2)]).unwrap(), RuntimeData::Int(-1) );
+        assert!( matches!( call("div", vec![RuntimeData::Int(1), RuntimeData::Int(0)]), Err(DynamicError::DivisionByZero) ) );
+        assert!( matches!( call("div", vec![RuntimeData::Int(i64::MIN), RuntimeData::Int(-1)]), Err(DynamicError::IntegerOverflow(_)) ) );
+    }
+
+    #[test]
+    fn to_int_should_truncate_and_reject_non_finite() {
+        assert_eq!( call("to_int", vec![RuntimeData::Number(-2.7)]).unwrap(), RuntimeData::Int(-2) );
+        assert!( matches!( call("to_int", vec![RuntimeData::Number(f64::NAN)]), Err(DynamicError::CannotConvertToInt(_)) ) );
+        assert!( matches!( call("to_int", vec![RuntimeData::Number(1e19)]), Err(DynamicError::CannotConvertToInt(_)) ) );
+    }
+
+    fn ratio(s : &str) -> RuntimeData {
+        RuntimeData::Rational(Rational::parse(s).unwrap())
+    }
+
+    #[test]
+    fn rational_arithmetic_should_be_exact() {
+        let sum = call("add", vec![ratio("0.1"), ratio("0.2")]).unwrap();
+
+        assert_eq!( sum, ratio("0.3") );
+        assert_eq!( call("div", vec![RuntimeData::Int(1), ratio("3")]).unwrap(), ratio("1/3") );
+        assert!( matches!( call("div", vec![ratio("1"), RuntimeData::Int(0)]), Err(DynamicError::DivisionByZero) ) );
+    }
+
+    #[test]
+    fn bigint_arithmetic_should_not_overflow() {
+        let output = call("mul", vec![RuntimeData::BigInt(BigInt::from_i64(i64::MAX)), RuntimeData::Int(i64::MAX)]).unwrap();
+
+        assert_eq!( output, RuntimeData::BigInt(BigInt::parse("85070591730234615847396907784232501249").unwrap()) );
+    }
+
+    #[test]
+    fn mixing_exact_numbers_with_number_should_produce_number() {
+        let output = call("add", vec![ratio("1/2"), RuntimeData::Number(0.25)]).unwrap();
+
+        assert_eq!( output, RuntimeData::Number(0.75) );
+    }
+
+    #[test]
+    fn to_rational_should_convert_number_exactly() {
+        assert_eq!( call("to_rational", vec![RuntimeData::Number(0.5)]).unwrap(), ratio("1/2") );
+        assert_eq!( call("numerator", vec![ratio("-6/4")]).unwrap(), RuntimeData::BigInt(BigInt::from_i64(-3)) );
+        assert_eq!( call("denominator", vec![ratio("-6/4")]).unwrap(), RuntimeData::BigInt(BigInt::from_i64(2)) );
+        assert!( matches!( call("to_int", vec![RuntimeData::BigInt(BigInt::parse("9223372036854775808").unwrap())]), Err(DynamicError::IntegerOverflow(_)) ) );
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use crate::runtime::*;
+use crate::bignum::{BigInt, Rational};
+use crate::compiling::error::DynamicError;
+
+use super::*;
+
+type R = Result<RuntimeData, DynamicError>;
+
+// NOTE:  Indices and lengths count chars (unicode scalar values) rather than bytes.  See list::concat for concat.
+pub fn register(table : &mut HashMap<String, NativeFn>) {
+    table.insert("string_length".into(), string_length);
+    table.insert("split".into(), split);
+    table.insert("join".into(), join);
+    table.insert("trim".into(), trim);
+    table.insert("to_upper".into(), to_upper);
+    table.insert("to_lower".into(), to_lower);
+    table.insert("contains".into(), contains);
+    table.insert("starts_with".into(), starts_with);
+    table.insert("ends_with".into(), ends_with);
+    table.insert("replace".into(), replace);
+    table.insert("substring".into(), substring);
+    table.insert("to_number".into(), to_number);
+    table.insert("number_to_string".into(), number_to_string);
+    table.insert("symbol_to_string".into(), symbol_to_string);
+    table.insert("string_to_symbol".into(), string_to_symbol);
+    table.insert("to_string".into(), to_string);
+    table.insert("chars".into(), chars);
+    table.insert("from_chars".into(), from_chars);
+    table.insert("char_to_int".into(), char_to_int);
+    table.insert("int_to_char".into(), int_to_char);
+}
+
+fn string_length(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("string_length", 1, &params)?;
+    Ok(RuntimeData::Int(string(heap, &params[0])?.chars().count() as i64))
+}
+
+// NOTE:  split(String, Separator) where an empty separator splits the string into single chars.
+fn split(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("split", 2, &params)?;
+    let s = string(heap, &params[0])?;
+    let sep = string(heap, &params[1])?;
+    let parts = if sep.is_empty() {
+        s.chars().map(|c| RuntimeData::String(c.to_string())).collect()
+    }
+    else {
+        s.split(sep.as_str()).map(|x| RuntimeData::String(x.into())).collect()
+    };
+    Ok(RuntimeData::List(parts))
+}
+
+// NOTE:  join(ListOfStrings, Separator)
+fn join(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("join", 2, &params)?;
+    let heap : &Heap = heap;
+    let parts = list(heap, &params[0])?.iter().map(|x| string(heap, x).map(|x| x.as_str())).collect::<Result<Vec<_>, _>>()?;
+    let sep = string(heap, &params[1])?;
+    Ok(RuntimeData::String(parts.join(sep)))
+}
+
+fn trim(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("trim", 1, &params)?;
+    Ok(RuntimeData::String(string(heap, &params[0])?.trim().into()))
+}
+
+fn to_upper(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_upper", 1, &params)?;
+    Ok(RuntimeData::String(string(heap, &params[0])?.to_uppercase()))
+}
+
+fn to_lower(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_lower", 1, &params)?;
+    Ok(RuntimeData::String(string(heap, &params[0])?.to_lowercase()))
+}
+
+fn contains(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("contains", 2, &params)?;
+    Ok(boolean(string(heap, &params[0])?.contains(string(heap, &params[1])?.as_str())))
+}
+
+fn starts_with(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("starts_with", 2, &params)?;
+    Ok(boolean(string(heap, &params[0])?.starts_with(string(heap, &params[1])?.as_str())))
+}
+
+fn ends_with(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("ends_with", 2, &params)?;
+    Ok(boolean(string(heap, &params[0])?.ends_with(string(heap, &params[1])?.as_str())))
+}
+
+// NOTE:  replace(String, From, To) replaces every occurrence of From.
+fn replace(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("replace", 3, &params)?;
+    let s = string(heap, &params[0])?;
+    let from = string(heap, &params[1])?;
+    let to = string(heap, &params[2])?;
+    Ok(RuntimeData::String(s.replace(from.as_str(), to)))
+}
+
+// NOTE:  substring(String, Start, Length)
+fn substring(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("substring", 3, &params)?;
+    let s = string(heap, &params[0])?;
+    let start = index(heap, &params[1])?;
+    let length = index(heap, &params[2])?;
+    let char_count = s.chars().count();
+    match start.checked_add(length) {
+        Some(end) if end <= char_count => { },
+        _ => return Err(DynamicError::IndexOutOfRange { index: start.saturating_add(length), length: char_count }),
+    }
+    Ok(RuntimeData::String(s.chars().skip(start).take(length).collect()))
+}
+
+fn to_number(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_number", 1, &params)?;
+    let s = string(heap, &params[0])?;
+    let t = s.trim();
+    if let Ok(x) = t.parse::<i64>() {
+        return Ok(RuntimeData::Int(x));
+    }
+    // NOTE:  Accepts the same suffixes as number literals so that number_to_string round trips.
+    let exact = match (t.strip_suffix('n'), t.strip_suffix('r')) {
+        (Some(x), _) => BigInt::parse(x).map(RuntimeData::BigInt),
+        (_, Some(x)) => Rational::parse(x).map(RuntimeData::Rational),
+        _ => BigInt::parse(t).map(RuntimeData::BigInt),
+    };
+    if let Some(x) = exact {
+        return Ok(x);
+    }
+    match t.parse::<f64>() {
+        Ok(x) => Ok(RuntimeData::Number(x)),
+        Err(_) => Err(DynamicError::CannotParseNumber(s.clone())),
+    }
+}
+
+fn symbol_to_string(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("symbol_to_string", 1, &params)?;
+    Ok(RuntimeData::String(symbol_name(heap, &params[0])?.into()))
+}
+
+fn string_to_symbol(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("string_to_symbol", 1, &params)?;
+    let s = string(heap, &params[0])?.clone();
+    Ok(symbol(heap, &s))
+}
+
+fn to_string(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("to_string", 1, &params)?;
+    Ok(RuntimeData::String(show(heap, &params[0], false)?))
+}
+
+fn chars(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("chars", 1, &params)?;
+    Ok(RuntimeData::List(string(heap, &params[0])?.chars().map(RuntimeData::Char).collect()))
+}
+
+// NOTE:  from_chars(ListOfChars)
+fn from_chars(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("from_chars", 1, &params)?;
+    let heap : &Heap = heap;
+    let s = list(heap, &params[0])?.iter().map(|x| char(heap, x)).collect::<Result<String, _>>()?;
+    Ok(RuntimeData::String(s))
+}
+
+fn char_to_int(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("char_to_int", 1, &params)?;
+    Ok(RuntimeData::Int(char(heap, &params[0])? as i64))
+}
+
+fn int_to_char(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    arity("int_to_char", 1, &params)?;
+    let x = int(heap, &params[0])?;
+    let c = u32::try_from(x).ok().and_then(char::from_u32).ok_or(DynamicError::InvalidCodePoint(x))?;
+    Ok(RuntimeData::Char(c))
+}
+
+// NOTE:  number_to_string(Number) or number_to_string(Number, Options) where Options is a list containing any of
+// {precision, Digits}, {width, Width}, and scientific.
+fn number_to_string(_ : &Natives, heap : &mut Heap, params : Vec<RuntimeData>) -> R {
+    if params.len() != 1 {
+        arity("number_to_string", 2, &params)?;
+    }
+
+    let x = number(heap, &params[0])?;
+    // NOTE:  Exact numbers are only converted to f64 when an option needs a float format.
+    let exact = match deref(heap, &params[0])? {
+        RuntimeData::Int(i) => Some(i.to_string()),
+        RuntimeData::BigInt(i) => Some(format!("{}n", i)),
+        RuntimeData::Rational(r) => Some(format!("{}r", r)),
+        _ => None,
+    };
+
+    let mut precision = None;
+    let mut width = 0;
+    let mut scientific = false;
+
+    if params.len() == 2 {
+        for option in list(heap, &params[1])? {
+            match deref(heap, option)? {
+                RuntimeData::Symbol(s) if heap.symbols().name(*s) == "scientific" => { scientific = true; },
+                RuntimeData::Tuple(t) if t.len() == 2 => {
+                    match symbol_name(heap, &t[0])? {
+                        "precision" => { precision = Some(format_option(heap, "precision", &t[1])?); },
+                        "width" => { width = format_option(heap, "width", &t[1])?; },
+                        s => { return Err(DynamicError::UnknownOption { name: "number_to_string".into(), option: s.into() }); },
+                    }
+                },
+                data => { return Err(DynamicError::UnknownOption { name: "number_to_string".into(), option: kind(data).into() }); },
+            }
+        }
+    }
+
+    let ret = match (precision, scientific, exact) {
+        (None, false, Some(x)) => format!("{:>width$}", x, width = width),
+        (Some(p), true, _) => format!("{:>width$.p$e}", x, width = width, p = p),
+        (Some(p), false, _) => format!("{:>width$.p$}", x, width = width, p = p),
+        (None, true, _) => format!("{:>width$e}", x, width = width),
+        (None, false, None) => format!("{:>width$}", x, width = width),
+    };
+
+    Ok(RuntimeData::String(ret))
+}
+
+const MAX_FORMAT_OPTION : usize = 1024;
+
+// NOTE:  Digits and Width are limited so that the string stays a reasonable size (and within what format! accepts).
+fn format_option(heap : &Heap, option : &str, data : &RuntimeData) -> Result<usize, DynamicError> {
+    let value = index(heap, data)?;
+    if value > MAX_FORMAT_OPTION {
+        return Err(DynamicError::OptionOutOfRange { name: "number_to_string".into(), option: option.into(), value, max: MAX_FORMAT_OPTION });
+    }
+    Ok(value)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn s(x : &str) -> RuntimeData {
+        RuntimeData::String(x.into())
+    }
+
+    fn call(name : &str, params : Vec<RuntimeData>) -> R {
+        Natives::new().call(name, &mut Heap::new(), params)
+    }
+
+    #[test]
+    fn string_length_should_count_chars() {
+        let output = call("string_length", vec![s("héllo😀")]).unwrap();
+
+        assert_eq!( output, RuntimeData::Int(6) );
+    }
+
+    #[test]
+    fn substring_should_index_by_char() {
+        let output = call("substring", vec![s("😀héllo"), RuntimeData::Number(1.0), RuntimeData::Number(2.0)]).unwrap();
+
+        assert_eq!( output, s("hé") );
+    }
+
+    #[test]
+    fn substring_should_reject_out_of_range() {
+        let output = call("substring", vec![s("abc"), RuntimeData::Number(2.0), RuntimeData::Number(2.0)]);
+
+        assert!( matches!( output, Err(DynamicError::IndexOutOfRange { index: 4, length: 3 }) ) );
+    }
+
+    #[test]
+    fn substring_should_reject_overflowing_range() {
+        let output = call("substring", vec![s("abc"), RuntimeData::Number(1.0), RuntimeData::Number(u64::MAX as f64)]);
+
+        assert!( matches!( output, Err(DynamicError::IndexOutOfRange { index: usize::MAX, length: 3 }) ) );
+    }
+
+    #[test]
+    fn split_and_join_should_round_trip() {
+        let parts = call("split", vec![s("a,b,,c"), s(",")]).unwrap();
+
+        assert_eq!( parts, RuntimeData::List(vec![s("a"), s("b"), s(""), s("c")]) );
+
+        let output = call("join", vec![parts, s(",")]).unwrap();
+
+        assert_eq!( output, s("a,b,,c") );
+    }
+
+    #[test]
+    fn to_number_should_distinguish_ints() {
+        assert_eq!( call("to_number", vec![s(" 9007199254740993 ")]).unwrap(), RuntimeData::Int(9007199254740993) );
+        assert_eq!( call("to_number", vec![s("1.0")]).unwrap(), RuntimeData::Number(1.0) );
+    }
+
+    #[test]
+    fn exact_numbers_should_round_trip_through_strings() {
+        for input in ["-123456789012345678901234567890n", "-1/3r", "5r"] {
+            let number = call("to_number", vec![s(input)]).unwrap();
+            let output = call("number_to_string", vec![number]).unwrap();
+
+            assert_eq!( output, s(input) );
+        }
+    }
+
+    #[test]
+    fn to_string_should_quote_only_nested_strings() {
+        let mut heap = Heap::new();
+        let ok = symbol(&mut heap, "ok");
+        let input = RuntimeData::Tuple(vec![ok, RuntimeData::List(vec![s("a"), RuntimeData::Char('b'), RuntimeData::Number(1.0), RuntimeData::Int(2)])]);
+
+        let nested = Natives::new().call("to_string", &mut heap, vec![input]).unwrap();
+        let plain = call("to_string", vec![s("a")]).unwrap();
+
+        assert_eq!( nested, s(r#"{ok, ["a", 'b', 1.0, 2]}"#) );
+        assert_eq!( plain, s("a") );
+    }
+
+    #[test]
+    fn chars_and_from_chars_should_round_trip() {
+        let output = call("chars", vec![s("hé😀")]).unwrap();
+
+        assert_eq!( output, RuntimeData::List(vec![RuntimeData::Char('h'), RuntimeData::Char('é'), RuntimeData::Char('😀')]) );
+        assert_eq!( call("from_chars", vec![output]).unwrap(), s("hé😀") );
+    }
+
+    #[test]
+    fn int_to_char_should_reject_surrogate() {
+        assert_eq!( call("int_to_char", vec![RuntimeData::Int(0x1F600)]).unwrap(), RuntimeData::Char('😀') );
+        assert!( matches!( call("int_to_char", vec![RuntimeData::Int(0xD800)]), Err(DynamicError::InvalidCodePoint(0xD800)) ) );
+    }
+
+    #[test]
+    fn to_number_should_report_bad_input() {
+        let output = call("to_number", vec![s("12abc")]);
+
+        assert!( matches!( output, Err(DynamicError::CannotParseNumber(x)) if x == "12abc" ) );
+    }
+
+    #[test]
+    fn number_to_string_should_apply_options() {
+        let mut heap = Heap::new();
+        let options = RuntimeData::List(vec![ RuntimeData::Tuple(vec![symbol(&mut heap, "precision"), RuntimeData::Number(2.0)])
+                                            , RuntimeData::Tuple(vec![symbol(&mut heap, "width"), RuntimeData::Number(6.0)])
+                                            ]);
+
+        let output = Natives::new().call("number_to_string", &mut heap, vec![RuntimeData::Number(3.14159), options]).unwrap();
+
+        assert_eq!( output, s("  3.14") );
+    }
+
+    #[test]
+    fn number_to_string_should_reject_unknown_option() {
+        let mut heap = Heap::new();
+        let options = RuntimeData::List(vec![symbol(&mut heap, "hex")]);
+
+        let output = Natives::new().call("number_to_string", &mut heap, vec![RuntimeData::Number(1.0), options]);
+
+        assert!( matches!( output, Err(DynamicError::UnknownOption { .. }) ) );
+    }
+
+    #[test]
+    fn number_to_string_should_reject_huge_width_and_precision() {
+        let mut heap = Heap::new();
+        let width = RuntimeData::List(vec![RuntimeData::Tuple(vec![symbol(&mut heap, "width"), RuntimeData::Int(70000)])]);
+        let precision = RuntimeData::List(vec![RuntimeData::Tuple(vec![symbol(&mut heap, "precision"), RuntimeData::Int(1 << 40)])]);
+
+        let output = Natives::new().call("number_to_string", &mut heap, vec![RuntimeData::Number(1.0), width]);
+        assert!( matches!( output, Err(DynamicError::OptionOutOfRange { value: 70000, .. }) ) );
+
+        let output = Natives::new().call("number_to_string", &mut heap, vec![RuntimeData::Number(1.0), precision]);
+        assert!( matches!( output, Err(DynamicError::OptionOutOfRange { .. }) ) );
+    }
+
+    #[test]
+    fn concat_should_join_strings() {
+        let output = call("concat", vec![s("a"), s("b"), s("c")]).unwrap();
+
+        assert_eq!( output, s("abc") );
+    }
+}
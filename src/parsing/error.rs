@@ -0,0 +1,35 @@
+
+use motif::MatchError;
+
+#[derive(Debug)]
+pub enum TokenizeError {
+    Match(MatchError),
+    UnknownEscape { position : usize, escape : char },
+    InvalidUnicodeEscape { position : usize, value : String },
+    InvalidHexEscape { position : usize, value : String },
+    MultiLineStringClosingNotOnOwnLine { position : usize },
+    MultiLineStringInsufficientIndent { position : usize, line : usize },
+}
+
+impl std::fmt::Display for TokenizeError {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TokenizeError::Match(e) => write!(f, "tokenize failed: {:?}", e),
+            TokenizeError::UnknownEscape { position, escape } => write!(f, "unknown escape \\{} at {}", escape, position),
+            TokenizeError::InvalidUnicodeEscape { position, value } => write!(f, "\\u{{{}}} at {} is not a unicode scalar value", value, position),
+            TokenizeError::InvalidHexEscape { position, value } => write!(f, "\\x{} at {} is not in the ascii range 00 to 7F", value, position),
+            TokenizeError::MultiLineStringClosingNotOnOwnLine { position } => 
+                write!(f, "multi-line string starting at {} must end with \"\"\" on its own line", position),
+            TokenizeError::MultiLineStringInsufficientIndent { position, line } => 
+                write!(f, "line {} of multi-line string starting at {} is indented less than its closing \"\"\"", line, position),
+        }
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+impl From<MatchError> for TokenizeError {
+    fn from(e : MatchError) -> Self {
+        TokenizeError::Match(e)
+    }
+}
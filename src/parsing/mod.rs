@@ -1,2 +1,3 @@
+pub mod error;
 pub mod tokenizer;
 pub mod parser;
\ No newline at end of file
@@ -7,13 +7,20 @@ use crate::ast::{ Token
                 , Expr
                 , Lit 
                 , Pat
+                , Bound
+                , RangeEnd
                 , Lambda
+                , Import
+                , TypeAnn
+                , StringPart
                 };
 
 pub fn parse(tokens : &Vec<Token>) -> Result<Top, MatchError> {
+    check_interpolations(tokens)?;
+
     let mut x = tokens.iter().enumerate();
 
-    let top = parse_top(&mut x)?;
+    let top = parse_file(&mut x)?;
 
     match x.next() {
         Some((i, _)) => Err(MatchError::Fatal(i)),
@@ -21,13 +28,183 @@ pub fn parse(tokens : &Vec<Token>) -> Result<Top, MatchError> {
     }
 }
 
+// NOTE:  Each expression embedded in an interpolated string has its own tokens, which have to be parsed completely.
+// Indices into the embedded tokens mean nothing outside of the string, so any failure (including in a string nested
+// inside of it) is reported at the index of the interpolated string itself.  This runs before the parse so that
+// parse_interpolation never sees an embedded expression that doesn't parse.
+fn check_interpolations(tokens : &[Token]) -> Result<(), MatchError> {
+    for (index, token) in tokens.iter().enumerate() {
+        if let Token::InterpolatedString(_, parts) = token {
+            for part in parts {
+                if let StringPart::Expr(_, embedded) = part {
+                    check_interpolations(embedded).and_then(|_| parse_embedded(embedded)).map_err(|_| MatchError::Fatal(index))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_embedded(tokens : &[Token]) -> Result<Expr, MatchError> {
+    let mut x = tokens.iter().enumerate();
+
+    match (parse_expr(&mut x), x.next()) {
+        (Ok(expr), None) => Ok(expr),
+        (Err(e), _) => Err(e),
+        (_, Some((i, _))) => Err(MatchError::Fatal(i)),
+    }
+}
+
+seq!(parse_file<'a>: &'a Token => Top = imports <= * parse_import
+                                       , exports <= ? parse_export
+                                       , lets <= * parse_let
+                                       , expr <= ? parse_expr
+                                       , {
+    Top { imports, exports, lets, expr }
+});
+
 seq!(parse_top<'a>: &'a Token => Top = lets <= * parse_let, expr <= ? parse_expr, {
-    Top { lets, expr }
+    Top { imports: vec![], exports: None, lets, expr }
+});
+
+seq!(parse_annotation<'a>: &'a Token => TypeAnn = Token::Colon(_), ann <= ! parse_type, { ann });
+
+group!(parse_type<'a>: &'a Token => TypeAnn = |input| {
+
+    pred!(is_fun<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "fun"
+        }
+        else {
+            false
+        }
+        
+        => { () } );
+
+    seq!(type_comma<'a>: &'a Token => TypeAnn = t <= parse_type, Token::Comma(_), { t });
+
+    seq!(type_list<'a>: &'a Token => Vec<TypeAnn> = ts <= * type_comma, last <= ? parse_type, {
+        let mut types = ts;
+        match last {
+            Some(t) => types.push(t),
+            None => { },
+        }
+        types
+    });
+
+    seq!(type_params<'a>: &'a Token => Vec<TypeAnn> = Token::LAngle(_), ts <= ! type_list, ! Token::RAngle(_), { ts });
+
+    seq!(named<'a>: &'a Token => TypeAnn = name <= Token::UpperSymbol(_, _), params <= ? type_params, {
+        if let Token::UpperSymbol(_, name) = name {
+            TypeAnn::Named(name.clone(), params.unwrap_or_default())
+        }
+        else {
+            panic!("reflexive fail");
+        }
+    });
+
+    seq!(var<'a>: &'a Token => TypeAnn = name <= Token::LowerSymbol(_, _), {
+        if let Token::LowerSymbol(_, name) = name {
+            TypeAnn::Var(name.clone())
+        }
+        else {
+            panic!("reflexive fail");
+        }
+    });
+
+    seq!(tuple<'a>: &'a Token => TypeAnn = Token::LCurl(_), ts <= ! type_list, ! Token::RCurl(_), { TypeAnn::Tuple(ts) });
+
+    seq!(fun<'a>: &'a Token => TypeAnn = is_fun
+                                       , ! Token::LParen(_)
+                                       , params <= ! type_list
+                                       , ! Token::RParen(_)
+                                       , ! Token::Colon(_)
+                                       , ret <= ! parse_type
+                                       , {
+        TypeAnn::Fun(params, Box::new(ret))
+    });
+
+    alt!(main<'a>: &'a Token => TypeAnn = fun | named | var | tuple);
+
+    main(input)
+});
+
+group!(parse_import<'a>: &'a Token => Import = |input| {
+
+    pred!(is_import<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "import"
+        }
+        else {
+            false
+        }
+        
+        => { () });
+
+    pred!(is_as<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "as"
+        }
+        else {
+            false
+        }
+        
+        => { () });
+
+    seq!(main<'a>: &'a Token => Import = is_import
+                                       , path <= ! Token::String(_, _)
+                                       , ! is_as
+                                       , name <= ! Token::UpperSymbol(_, _)
+                                       , ! Token::Semicolon(_)
+                                       , {
+        match (path, name) {
+            (Token::String(_, path), Token::UpperSymbol(_, name)) => Import { path: path.clone(), name: name.clone() },
+            _ => panic!("reflexive fail"),
+        }
+    });
+
+    main(input)
+});
+
+group!(parse_export<'a>: &'a Token => Vec<String> = |input| {
+
+    pred!(is_export<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "export"
+        }
+        else {
+            false
+        }
+        
+        => { () });
+
+    seq!(variable<'a>: &'a Token => String = variable <= Token::UpperSymbol(_, _), {
+        if let Token::UpperSymbol(_, var) = variable {
+            var.clone()
+        }
+        else {
+            panic!("reflexive fail");
+        }
+    });
+
+    seq!(variable_comma<'a>: &'a Token => String = var <= variable, Token::Comma(_), { var });
+
+    seq!(main<'a>: &'a Token => Vec<String> = is_export
+                                            , vs <= * variable_comma
+                                            , last <= ! variable
+                                            , ! Token::Semicolon(_)
+                                            , {
+        let mut vars = vs;
+        vars.push(last);
+        vars
+    });
+
+    main(input)
 });
 
 group!(parse_let<'a>: &'a Token => Let = |input| {
 
-    pred!(is_let<'a>: &'a Token => () = 
+    pred!(is_let<'a>: &'a Token => usize = 
         |_tok| if let Token::LowerSymbol(_, sym) = _tok {
             sym == "let"
         }
@@ -35,14 +212,21 @@ group!(parse_let<'a>: &'a Token => Let = |input| {
             false
         }
         
-        => { () });
+        => { position(_tok) });
 
-    seq!(main<'a>: &'a Token => Let = is_let
+    seq!(main<'a>: &'a Token => Let = position <= is_let
                                     , pattern <= ! parse_pattern
+                                    , ann <= ? parse_annotation
                                     , ! Token::Equal(_)
                                     , expr <= ! parse_expr
                                     , ! Token::Semicolon(_)
-                                    , { Let { pattern, expr } });
+                                    , {
+        let mut expr = expr;
+        if let (Pat::Variable(name), Expr::Literal(Lit::Lambda(lambda))) = (&pattern, &mut expr) {
+            lambda.name = Some(name.clone());
+        }
+        Let { position, pattern, ann, expr }
+    });
 
     main(input)
 });
@@ -60,8 +244,30 @@ group!(parse_pattern<'a>: &'a Token => Pat = |input| {
 
     seq!(pat_comma<'a>: &'a Token => Pat = pat <= parse_pattern, Token::Comma(_), { pat });
 
-    seq!(or_bar_pat<'a>: &'a Token => Box<Pat> = Token::OrBar(_), pat <= ! parse_pattern, { Box::new(pat) });
+    seq!(or_bar_suffix<'a>: &'a Token => Vec<Pat> = Token::OrBar(_)
+                                                  , ps <= * pat_comma 
+                                                  , last <= ? parse_pattern
+                                                  , {
+
+        let mut pats = ps;
+        match last {
+            Some(pat) => pats.push(pat),
+            None => { },
+        }
+        pats
+    });
+
+    seq!(or_bar_pat<'a>: &'a Token => (Box<Pat>, Vec<Pat>) = Token::OrBar(_)
+                                                           , pat <= ! parse_pattern
+                                                           , suffix <= ? or_bar_suffix
+                                                           , {
+        match suffix {
+            Some(suffix) => (Box::new(pat), suffix),
+            None => (Box::new(pat), vec![]),
+        }
+    });
 
+    // NOTE:  [Prefix, ... | Rest | Suffix, ...] where both the rest and suffix sections are optional.
     seq!(pat_list<'a>: &'a Token => Pat = Token::LSquare(_)
                                         , ps <= * pat_comma 
                                         , last <= ? parse_pattern
@@ -74,7 +280,10 @@ group!(parse_pattern<'a>: &'a Token => Pat = |input| {
             Some(pat) => pats.push(pat),
             None => { },
         }
-        Pat::List(pats, rest)
+        match rest {
+            Some((rest, suffix)) => Pat::List(pats, Some(rest), suffix),
+            None => Pat::List(pats, None, vec![]),
+        }
     });
 
     seq!(pat_tuple<'a>: &'a Token => Pat = Token::LCurl(_)
@@ -90,6 +299,25 @@ group!(parse_pattern<'a>: &'a Token => Pat = |input| {
         Pat::Tuple(pats)
     });
 
+    seq!(or_alternative<'a>: &'a Token => Pat = Token::OrBar(_), pat <= ! parse_pattern, { pat });
+
+    // NOTE:  Or patterns are surrounded by parens because OrBar already marks the rest of a list pattern.
+    seq!(pat_or<'a>: &'a Token => Pat = Token::LParen(_)
+                                      , first <= ! parse_pattern
+                                      , alternatives <= * or_alternative
+                                      , ! Token::RParen(_)
+                                      , {
+
+        if alternatives.len() == 0 {
+            first
+        }
+        else {
+            let mut pats = vec![first];
+            pats.extend(alternatives);
+            Pat::Or(pats)
+        }
+    });
+
     pred!(wild<'a>: &'a Token => Pat = 
         |_tok| if let Token::LowerSymbol(_, sym) = _tok {
             sym == "_"
@@ -100,15 +328,45 @@ group!(parse_pattern<'a>: &'a Token => Pat = |input| {
         
         => { Pat::Wild });
 
-    seq!(number<'a>: &'a Token => Pat = n <= Token::Number(_, _), { 
-        if let Token::Number(_, number) = n {
-            Pat::Number(*number) 
+    seq!(number<'a>: &'a Token => Pat = n <= Token::Number(_, _) | Token::Int(_, _) | Token::BigInt(_, _) | Token::Rational(_, _), { 
+        match n {
+            Token::Number(_, number) => Pat::Number(*number),
+            Token::Int(_, int) => Pat::Int(*int),
+            Token::BigInt(_, int) => Pat::BigInt(int.clone()),
+            Token::Rational(_, ratio) => Pat::Rational(ratio.clone()),
+            _ => panic!("reflexive fail"),
         }
-        else {
-            panic!("reflexive fail");
+    });
+
+    fn bound(n : &Token) -> Bound {
+        match n {
+            Token::Number(_, number) => Bound::Number(*number),
+            Token::Int(_, int) => Bound::Int(*int),
+            _ => panic!("reflexive fail"),
         }
+    }
+
+    // NOTE:  The end number is not fatal so that an open range like 0.. can fall through to open_range_end.
+    seq!(bounded_range_end<'a>: &'a Token => Option<RangeEnd> = op <= Token::DotDot(_) | Token::DotDotEqual(_)
+                                                               , n <= Token::Number(_, _) | Token::Int(_, _)
+                                                               , {
+        match op {
+            Token::DotDot(_) => Some(RangeEnd::Exclusive(bound(n))),
+            Token::DotDotEqual(_) => Some(RangeEnd::Inclusive(bound(n))),
+            _ => panic!("reflexive fail"),
+        }
+    });
+
+    seq!(open_range_end<'a>: &'a Token => Option<RangeEnd> = Token::DotDot(_), { None });
+
+    alt!(range_end<'a>: &'a Token => Option<RangeEnd> = bounded_range_end | open_range_end);
+
+    seq!(range_from<'a>: &'a Token => Pat = n <= Token::Number(_, _) | Token::Int(_, _), end <= range_end, { 
+        Pat::Range(Some(bound(n)), end)
     });
 
+    seq!(range_to<'a>: &'a Token => Pat = end <= bounded_range_end, { Pat::Range(None, end) });
+
     seq!(string<'a>: &'a Token => Pat = s <= Token::String(_, _), { 
         if let Token::String(_, string) = s {
             Pat::String(string.into()) 
@@ -118,6 +376,15 @@ group!(parse_pattern<'a>: &'a Token => Pat = |input| {
         }
     });
 
+    seq!(char_pat<'a>: &'a Token => Pat = c <= Token::Char(_, _), { 
+        if let Token::Char(_, c) = c {
+            Pat::Char(*c) 
+        }
+        else {
+            panic!("reflexive fail");
+        }
+    });
+
     seq!(symbol<'a>: &'a Token => Pat = symbol <= Token::LowerSymbol(_, _), { 
         if let Token::LowerSymbol(_, sym) = symbol {
             Pat::Symbol(sym.into()) 
@@ -137,15 +404,56 @@ group!(parse_pattern<'a>: &'a Token => Pat = |input| {
     });
 
 
-    alt!(main<'a>: &'a Token => Pat = at
+    seq!(pat_kvp<'a>: &'a Token => (Lit, Pat) = key <= parse_literal, ! Token::DRArrow(_), pat <= ! parse_pattern, { (key, pat) });
+
+    seq!(pat_kvp_comma<'a>: &'a Token => (Lit, Pat) = kvp <= pat_kvp, Token::Comma(_), { kvp });
+
+    seq!(pat_map<'a>: &'a Token => Pat = Token::PercentLCurl(_)
+                                       , kvps <= * pat_kvp_comma 
+                                       , last <= ? pat_kvp
+                                       , ! Token::RCurl(_)
+                                       , {
+        let mut kvps = kvps;
+        match last {
+            Some(kvp) => kvps.push(kvp),
+            None => { },
+        }
+        Pat::Map(kvps)
+    });
+
+    seq!(pin<'a>: &'a Token => Pat = Token::Caret(_), variable <= ! Token::UpperSymbol(_, _), { 
+        if let Token::UpperSymbol(_, var) = variable {
+            Pat::Pin(var.into()) 
+        }
+        else {
+            panic!("reflexive fail");
+        }
+    });
+
+    alt!(primary<'a>: &'a Token => Pat = at
+                                    | pin
                                     | wild
                                     | variable
                                     | symbol
                                     | string
+                                    | char_pat
+                                    | range_from
+                                    | range_to
                                     | number
                                     | pat_tuple
                                     | pat_list
+                                    | pat_map
+                                    | pat_or
                                     );
+
+    seq!(concat_rest<'a>: &'a Token => Pat = Token::PlusPlus(_), pat <= ! parse_pattern, { pat });
+
+    seq!(main<'a>: &'a Token => Pat = pat <= primary, rest <= ? concat_rest, {
+        match rest {
+            Some(rest) => Pat::StringConcat(Box::new(pat), Box::new(rest)),
+            None => pat,
+        }
+    });
     
     main(input)
 });
@@ -155,47 +463,118 @@ group!(parse_expr<'a>: &'a Token => Expr = |input| {
 
     seq!(expr_comma<'a>: &'a Token => Expr = expr <= parse_expr, Token::Comma(_), { expr });
 
-    seq!(param_list<'a>: &'a Token => Vec<Expr> = Token::LParen(_)
-                                                , es <= * expr_comma 
-                                                , last <= ? parse_expr
-                                                , ! Token::RParen(_)
-                                                , {
+    seq!(param_list<'a>: &'a Token => (usize, Vec<Expr>) = l <= Token::LParen(_)
+                                                         , es <= * expr_comma 
+                                                         , last <= ? parse_expr
+                                                         , ! Token::RParen(_)
+                                                         , {
 
         let mut exprs = es;
         match last {
             Some(expr) => exprs.push(expr),
             None => { },
         }
-        exprs
+        (position(l), exprs)
     });
 
-    alt!(main<'a>: &'a Token => Expr = lit);
+    pred!(is_try<'a>: &'a Token => usize = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "try"
+        }
+        else {
+            false
+        }
+        
+        => { position(_tok) } );
 
-    seq!(call<'a>: &'a Token => Expr = m <= main, calls <= * param_list, {
-        if calls.len() == 0 {
-            m
+    pred!(is_catch<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "catch"
         }
         else {
-            let mut r = None;
-            for call in calls {
-                if let Some(prev) = r {
-                    r = Some(Expr::Call(Box::new(prev), call));
-                }
-                else {
-                    r = Some(Expr::Call(Box::new(m.clone()), call));
-                }
-            }
-            if let Some(ret) = r {
-                ret
-            }
-            else {
-                panic!("return value isn't there");
-            }
+            false
+        }
+        
+        => { () } );
+
+    seq!(catch<'a>: &'a Token => (Pat, Expr) = is_catch
+                                             , pattern <= ! parse_pattern
+                                             , ! Token::DRArrow(_)
+                                             , expr <= ! parse_expr
+                                             , { (pattern, expr) });
+
+    // NOTE:  try { Block } catch P => E catch P => E
+    seq!(try_catch<'a>: &'a Token => Expr = start <= is_try
+                                          , ! Token::LCurl(_)
+                                          , block <= ! parse_top
+                                          , ! Token::RCurl(_)
+                                          , handlers <= * catch
+                                          , {
+        Expr::Try(start, Box::new(block), handlers)
+    });
+
+    alt!(main<'a>: &'a Token => Expr = try_catch | parse_interpolation | lit);
+
+    seq!(params<'a>: &'a Token => Postfix = ps <= param_list, { Postfix::Params(ps.0, ps.1) });
+
+    // NOTE:  X.f is the same as X.f() so that chains like Xs.reverse.length read naturally.
+    seq!(method<'a>: &'a Token => Postfix = dot <= Token::Dot(_)
+                                          , name <= ! Token::LowerSymbol(_, _)
+                                          , ps <= ? param_list
+                                          , {
+        let name = if let Token::LowerSymbol(_, sym) = name {
+            sym.clone()
         }
+        else {
+            panic!("reflexive fail");
+        };
+        Postfix::Method(position(dot), name, ps.map(|ps| ps.1).unwrap_or_default())
+    });
 
+    seq!(propagate<'a>: &'a Token => Postfix = q <= Token::Question(_), { Postfix::Propagate(position(q)) });
+
+    alt!(postfix<'a>: &'a Token => Postfix = params | method | propagate);
+
+    // NOTE:  Calls, method calls, and ? bind left to right with the same precedence, so X.f(Y)(Z) calls the result
+    // of f(X, Y) with Z and f(X)?.g unwraps the result of f(X) before calling g.
+    seq!(call<'a>: &'a Token => Expr = m <= main, posts <= * postfix, {
+        posts.into_iter().fold(m, |acc, post| match post {
+            Postfix::Params(start, ps) => Expr::Call(start, Box::new(acc), ps),
+            Postfix::Method(start, name, ps) => Expr::Method(start, Box::new(acc), name, ps),
+            Postfix::Propagate(start) => Expr::Propagate(start, Box::new(acc)),
+        })
+    });
+
+    seq!(pipe_stage<'a>: &'a Token => (usize, Expr) = arrow <= Token::SRArrow(_), stage <= ! call, { (position(arrow), stage) });
+
+    // NOTE:  X -> f(Y) -> g is sugar for g(f(X, Y)).  The piped value becomes the first parameter of the
+    // outermost call in each stage, or the only parameter when the stage isn't a call.  For a method call it goes
+    // before the written parameters, so X -> Ys.f(Z) is Ys.f(X, Z).
+    seq!(pipeline<'a>: &'a Token => Expr = head <= call, stages <= * pipe_stage, {
+        stages.into_iter().fold(head, |acc, (arrow, stage)| match stage {
+            Expr::Call(start, func, mut params) => {
+                params.insert(0, acc);
+                Expr::Call(start, func, params)
+            },
+            Expr::Method(start, receiver, name, mut params) => {
+                params.insert(0, acc);
+                Expr::Method(start, receiver, name, params)
+            },
+            stage => Expr::Call(arrow, Box::new(stage), vec![acc]),
+        })
     });
 
-    call(input)
+    seq!(equal_rhs<'a>: &'a Token => (usize, Expr) = op <= Token::DoubleEqual(_), rhs <= ! pipeline, { (position(op), rhs) });
+
+    // NOTE:  A == B is sugar for calling the == native.
+    seq!(equality<'a>: &'a Token => Expr = lhs <= pipeline, rhs <= ? equal_rhs, {
+        match rhs {
+            Some((op, rhs)) => Expr::Call(op, Box::new(Expr::Literal(Lit::Symbol("==".into()))), vec![lhs, rhs]),
+            None => lhs,
+        }
+    });
+
+    equality(input)
 });
 // match
 // let
@@ -213,6 +592,42 @@ let X = fun(A) {
 
 */
 
+fn position( token : &Token ) -> usize {
+    match token {
+        Token::LowerSymbol(meta, _) | Token::InterpolatedString(meta, _) => meta.start,
+        Token::LParen(meta) | Token::Dot(meta) | Token::Question(meta) | Token::SRArrow(meta) | Token::DoubleEqual(meta) => meta.start,
+        _ => 0,
+    }
+}
+
+// NOTE:  Each postfix keeps the position of the token that starts it.
+enum Postfix {
+    Params(usize, Vec<Expr>),
+    Method(usize, String, Vec<Expr>),
+    Propagate(usize),
+}
+
+group!(parse_interpolation<'a>: &'a Token => Expr = |input| {
+    seq!(main<'a>: &'a Token => (usize, &'a Vec<StringPart>) = t <= Token::InterpolatedString(_, _), {
+        if let Token::InterpolatedString(_, parts) = t {
+            (position(t), parts)
+        }
+        else {
+            panic!("reflexive fail");
+        }
+    });
+
+    let (start, parts) = main(input)?;
+    let mut exprs = vec![];
+    for part in parts {
+        match part {
+            StringPart::Literal(s) => exprs.push(Expr::Literal(Lit::String(s.clone()))),
+            StringPart::Expr(_, tokens) => exprs.push(parse_embedded(tokens)?),
+        }
+    }
+    Ok(Expr::Interpolate(start, exprs))
+});
+
 group!(parse_literal<'a>: &'a Token => Lit = |input| {
 
     seq!(lit_comma<'a>: &'a Token => Lit = lit <= parse_literal, Token::Comma(_), { lit });
@@ -245,12 +660,31 @@ group!(parse_literal<'a>: &'a Token => Lit = |input| {
         Lit::Tuple(lits)
     });
 
-    seq!(number<'a>: &'a Token => Lit = n <= Token::Number(_, _), { 
-        if let Token::Number(_, number) = n {
-            Lit::Number(*number) 
+    seq!(lit_kvp<'a>: &'a Token => (Lit, Lit) = key <= parse_literal, ! Token::DRArrow(_), value <= ! parse_literal, { (key, value) });
+
+    seq!(lit_kvp_comma<'a>: &'a Token => (Lit, Lit) = kvp <= lit_kvp, Token::Comma(_), { kvp });
+
+    seq!(lit_map<'a>: &'a Token => Lit = Token::PercentLCurl(_)
+                                       , kvps <= * lit_kvp_comma 
+                                       , last <= ? lit_kvp
+                                       , ! Token::RCurl(_)
+                                       , {
+
+        let mut kvps = kvps;
+        match last {
+            Some(kvp) => kvps.push(kvp),
+            None => { },
         }
-        else {
-            panic!("reflexive fail");
+        Lit::Map(kvps)
+    });
+
+    seq!(number<'a>: &'a Token => Lit = n <= Token::Number(_, _) | Token::Int(_, _) | Token::BigInt(_, _) | Token::Rational(_, _), { 
+        match n {
+            Token::Number(_, number) => Lit::Number(*number),
+            Token::Int(_, int) => Lit::Int(*int),
+            Token::BigInt(_, int) => Lit::BigInt(int.clone()),
+            Token::Rational(_, ratio) => Lit::Rational(ratio.clone()),
+            _ => panic!("reflexive fail"),
         }
     });
 
@@ -263,26 +697,38 @@ group!(parse_literal<'a>: &'a Token => Lit = |input| {
         }
     });
 
-    seq!(symbol<'a>: &'a Token => Lit = symbol <= Token::LowerSymbol(_, _), { 
-        if let Token::LowerSymbol(_, sym) = symbol {
-            Lit::Symbol(sym.into()) 
+    seq!(char_lit<'a>: &'a Token => Lit = c <= Token::Char(_, _), { 
+        if let Token::Char(_, c) = c {
+            Lit::Char(*c) 
         }
         else {
             panic!("reflexive fail");
         }
     });
 
-    seq!(variable<'a>: &'a Token => Lit = variable <= Token::UpperSymbol(_, _), { 
-        if let Token::UpperSymbol(_, var) = variable {
-            Lit::Variable(var.into()) 
+    seq!(symbol<'a>: &'a Token => Lit = symbol <= Token::LowerSymbol(_, _), { 
+        if let Token::LowerSymbol(_, sym) = symbol {
+            Lit::Symbol(sym.into()) 
         }
         else {
             panic!("reflexive fail");
         }
     });
 
+    seq!(member<'a>: &'a Token => &'a Token = Token::Dot(_), member <= Token::UpperSymbol(_, _), { member });
 
-    pred!(is_fun<'a>: &'a Token => () = 
+    // NOTE:  M.Name refers to Name exported from the module imported as M.  Lower case names after a dot are method
+    // calls (see parse_expr).
+    seq!(variable<'a>: &'a Token => Lit = variable <= Token::UpperSymbol(_, _), member <= ? member, { 
+        match (variable, member) {
+            (Token::UpperSymbol(_, var), None) => Lit::Variable(var.into()),
+            (Token::UpperSymbol(_, module), Some(Token::UpperSymbol(_, var))) => Lit::Variable(format!("{}.{}", module, var)),
+            _ => panic!("reflexive fail"),
+        }
+    });
+
+
+    pred!(is_fun<'a>: &'a Token => usize = 
         |_tok| if let Token::LowerSymbol(_, sym) = _tok {
             sym == "fun"
         }
@@ -290,41 +736,85 @@ group!(parse_literal<'a>: &'a Token => Lit = |input| {
             false
         }
         
-        => { () } );
+        => { position(_tok) } );
 
-    seq!(pat_comma<'a>: &'a Token => Pat = pat <= parse_pattern, Token::Comma(_), { pat });
+    seq!(param<'a>: &'a Token => (Pat, Option<TypeAnn>) = pat <= parse_pattern, ann <= ? parse_annotation, { (pat, ann) });
 
-    seq!(parse_params<'a>: &'a Token => Vec<Pat> = Token::LParen(_)
-                                                 , ps <= * pat_comma 
-                                                 , last <= ? parse_pattern
-                                                 , ! Token::RParen(_)
-                                                 , {
+    seq!(param_comma<'a>: &'a Token => (Pat, Option<TypeAnn>) = p <= param, Token::Comma(_), { p });
 
-        let mut pats = ps;
+    seq!(parse_params<'a>: &'a Token => Vec<(Pat, Option<TypeAnn>)> = Token::LParen(_)
+                                                                    , ps <= * param_comma 
+                                                                    , last <= ? param
+                                                                    , ! Token::RParen(_)
+                                                                    , {
+
+        let mut params = ps;
         match last {
-            Some(pat) => pats.push(pat),
+            Some(p) => params.push(p),
             None => { },
         }
-        pats
+        params
+    });
+
+    pred!(is_requires<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "requires"
+        }
+        else {
+            false
+        }
+        
+        => { () } );
+
+    pred!(is_ensures<'a>: &'a Token => () = 
+        |_tok| if let Token::LowerSymbol(_, sym) = _tok {
+            sym == "ensures"
+        }
+        else {
+            false
+        }
+        
+        => { () } );
+
+    seq!(requires<'a>: &'a Token => Expr = is_requires, clause <= ! parse_expr, { clause });
+
+    // NOTE:  ensures R -> Clause where R is the return value.
+    seq!(ensures<'a>: &'a Token => (String, Expr) = is_ensures
+                                                  , var <= ! Token::UpperSymbol(_, _)
+                                                  , ! Token::SRArrow(_)
+                                                  , clause <= ! parse_expr
+                                                  , {
+        if let Token::UpperSymbol(_, var) = var {
+            (var.clone(), clause)
+        }
+        else {
+            panic!("reflexive fail");
+        }
     });
 
-    seq!(lambda<'a>: &'a Token => Lit = is_fun
+    seq!(lambda<'a>: &'a Token => Lit = position <= is_fun
                                        , params <= ! parse_params
+                                       , ret_ann <= ? parse_annotation
+                                       , requires <= * requires
+                                       , ensures <= * ensures
                                        , ! Token::LCurl(_)
                                        , body <= ! parse_top
                                        , ! Token::RCurl(_)
                                        , {
 
-        Lit::Lambda(Lambda { params, body: Box::new(body) } )
+        let (params, param_anns) = params.into_iter().unzip();
+        Lit::Lambda(Lambda { name: None, position, params, param_anns, ret_ann, requires, ensures, body: Box::new(body) } )
     });
 
     alt!(main<'a>: &'a Token => Lit = number 
                                     | string 
+                                    | char_lit
                                     | lambda
                                     | symbol 
                                     | variable
                                     | lit_list
                                     | lit_tuple
+                                    | lit_map
                                     );
 
     main(input)
@@ -357,8 +847,284 @@ mod test {
         };
     }
 
-    test_parse!(should_parse_number: "1.0" => Top { lets, expr } => {
+    test_parse!(should_parse_number: "1.0" => Top { lets, expr, .. } => {
         assert_eq!( lets.len(), 0 );
         assert!( matches!( expr, Some(Expr::Literal(Lit::Number(1.0))) ) );
     });
+
+    test_parse!(should_parse_or_pattern: "let (a | { X, b } | [X]) = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::Or(ps) if ps.len() == 3 ) );
+        assert!( matches!( &lets[0].pattern, Pat::Or(ps) if matches!( ps[0], Pat::Symbol(_) ) ) );
+        assert!( matches!( &lets[0].pattern, Pat::Or(ps) if matches!( ps[1], Pat::Tuple(_) ) ) );
+        assert!( matches!( &lets[0].pattern, Pat::Or(ps) if matches!( ps[2], Pat::List(_, None, _) ) ) );
+    });
+
+    test_parse!(should_parse_parenthesized_pattern: "let (X) = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::Variable(x) if x == "X" ) );
+    });
+
+    test_parse!(should_parse_exclusive_range_pattern: "let 200..300 = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( lets[0].pattern, Pat::Range(Some(Bound::Int(200)), Some(RangeEnd::Exclusive(Bound::Int(300)))) ) );
+    });
+
+    test_parse!(should_parse_inclusive_range_pattern: "let 200..=299 = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( lets[0].pattern, Pat::Range(Some(Bound::Int(200)), Some(RangeEnd::Inclusive(Bound::Int(299)))) ) );
+    });
+
+    test_parse!(should_parse_open_range_pattern: "let 0.. = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( lets[0].pattern, Pat::Range(Some(Bound::Int(0)), None) ) );
+    });
+
+    test_parse!(should_parse_upper_range_pattern: "let ..=-1 = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( lets[0].pattern, Pat::Range(None, Some(RangeEnd::Inclusive(Bound::Int(-1)))) ) );
+    });
+
+    test_parse!(should_parse_string_concat_pattern: r#"let "GET " ++ Path ++ ".json" = 1;"# => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        let (prefix, rest) = match &lets[0].pattern {
+            Pat::StringConcat(prefix, rest) => (prefix, rest),
+            x => panic!("expected string concat but found {:?}", x),
+        };
+        assert!( matches!( &**prefix, Pat::String(x) if x == "GET " ) );
+        assert!( matches!( &**rest, Pat::StringConcat(a, b) if matches!( (&**a, &**b), (Pat::Variable(_), Pat::String(_)) ) ) );
+    });
+
+    test_parse!(should_parse_list_pattern_with_rest: "let [H | T] = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::List(ps, Some(_), ss) if ps.len() == 1 && ss.len() == 0 ) );
+    });
+
+    test_parse!(should_parse_list_pattern_with_suffix: "let [| Init | Last] = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::List(ps, Some(_), ss) if ps.len() == 0 && ss.len() == 1 ) );
+    });
+
+    test_parse!(should_parse_list_pattern_with_middle: "let [A, B | Middle | Y, Z] = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::List(ps, Some(_), ss) if ps.len() == 2 && ss.len() == 2 ) );
+    });
+
+    test_parse!(should_parse_pin_pattern: "let { ^X, Y } = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::Tuple(ps) if matches!( &ps[0], Pat::Pin(x) if x == "X" ) ) );
+    });
+
+    test_parse!(should_parse_equality: "X == [1]" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let (func, params) = match expr {
+            Some(Expr::Call(_, func, params)) => (func, params),
+            x => panic!("expected call but found {:?}", x),
+        };
+        assert!( matches!( *func, Expr::Literal(Lit::Symbol(x)) if x == "==" ) );
+        assert_eq!( params.len(), 2 );
+    });
+
+    test_parse!(should_parse_map_literal: "%{ a => 1, \"b\" => [2] }" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        assert!( matches!( expr, Some(Expr::Literal(Lit::Map(kvps))) if kvps.len() == 2 ) );
+    });
+
+    test_parse!(should_parse_map_pattern: "let %{ a => X, b => [Y] } = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::Map(kvps) if kvps.len() == 2 ) );
+    });
+
+    test_parse!(should_parse_exact_number_pattern: "let {12n, 1/3r} = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::Tuple(ps) if matches!( ps[..], [Pat::BigInt(_), Pat::Rational(_)] ) ) );
+    });
+
+    test_parse!(should_parse_char: "let ['a', C] = ['\\n', 'b'];" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        assert!( matches!( &lets[0].pattern, Pat::List(ps, None, _) if matches!( ps[..], [Pat::Char('a'), Pat::Variable(_)] ) ) );
+        assert!( matches!( &lets[0].expr, Expr::Literal(Lit::List(ls)) if matches!( ls[..], [Lit::Char('\n'), Lit::Char('b')] ) ) );
+    });
+
+    test_parse!(should_parse_interpolation: "\"Hello ${Name}, you are ${length(Xs)}\"" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let parts = match expr {
+            Some(Expr::Interpolate(_, parts)) => parts,
+            _ => panic!("expected interpolation"),
+        };
+        assert_eq!( parts.len(), 4 );
+        assert!( matches!( &parts[0], Expr::Literal(Lit::String(x)) if x == "Hello " ) );
+        assert!( matches!( &parts[1], Expr::Literal(Lit::Variable(x)) if x == "Name" ) );
+        assert!( matches!( &parts[3], Expr::Call(_, _, ps) if ps.len() == 1 ) );
+    });
+
+    test_parse!(should_parse_pipeline: "X -> f(Y) -> g" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let (func, params) = match expr {
+            Some(Expr::Call(_, func, params)) => (func, params),
+            _ => panic!("expected call"),
+        };
+        assert!( matches!( *func, Expr::Literal(Lit::Symbol(ref x)) if x == "g" ) );
+        assert_eq!( params.len(), 1 );
+        let (inner, inner_params) = match &params[0] {
+            Expr::Call(_, func, params) => (func, params),
+            _ => panic!("expected inner call"),
+        };
+        assert!( matches!( **inner, Expr::Literal(Lit::Symbol(ref x)) if x == "f" ) );
+        assert_eq!( inner_params.len(), 2 );
+        assert!( matches!( &inner_params[0], Expr::Literal(Lit::Variable(x)) if x == "X" ) );
+        assert!( matches!( &inner_params[1], Expr::Literal(Lit::Variable(x)) if x == "Y" ) );
+    });
+
+    test_parse!(should_parse_pipeline_tighter_than_equality: "X -> f == Y -> g" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let params = match expr {
+            Some(Expr::Call(_, func, params)) if matches!( *func, Expr::Literal(Lit::Symbol(ref x)) if x == "==" ) => params,
+            _ => panic!("expected equality"),
+        };
+        assert!( matches!( &params[0], Expr::Call(_, f, ps) if ps.len() == 1 && matches!( **f, Expr::Literal(Lit::Symbol(ref x)) if x == "f" ) ) );
+        assert!( matches!( &params[1], Expr::Call(_, g, ps) if ps.len() == 1 && matches!( **g, Expr::Literal(Lit::Symbol(ref x)) if x == "g" ) ) );
+    });
+
+    test_parse!(should_parse_method_call: "Xs.map(F).length" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let (receiver, name, params) = match expr {
+            Some(Expr::Method(_, receiver, name, params)) => (receiver, name, params),
+            _ => panic!("expected method call"),
+        };
+        assert_eq!( name, "length" );
+        assert_eq!( params.len(), 0 );
+        assert!( matches!( *receiver, Expr::Method(_, ref r, ref n, ref ps) 
+                                        if n == "map" 
+                                        && ps.len() == 1 
+                                        && matches!( **r, Expr::Literal(Lit::Variable(ref x)) if x == "Xs" ) ) );
+    });
+
+    test_parse!(should_parse_method_call_tighter_than_pipeline: "X -> f.g" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        assert!( matches!( expr, Some(Expr::Method(_, ref r, ref n, ref ps)) 
+                                    if n == "g" 
+                                    && matches!( **r, Expr::Literal(Lit::Symbol(ref x)) if x == "f" )
+                                    && matches!( &ps[..], [Expr::Literal(Lit::Variable(x))] if x == "X" ) ) );
+    });
+
+    test_parse!(should_parse_pipeline_into_method_call: "X -> Ys.f(Z)" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let (receiver, name, params) = match expr {
+            Some(Expr::Method(_, receiver, name, params)) => (receiver, name, params),
+            _ => panic!("expected method call"),
+        };
+        assert_eq!( name, "f" );
+        assert!( matches!( *receiver, Expr::Literal(Lit::Variable(ref x)) if x == "Ys" ) );
+        assert!( matches!( &params[..], [Expr::Literal(Lit::Variable(x)), Expr::Literal(Lit::Variable(z))] if x == "X" && z == "Z" ) );
+    });
+
+    test_parse!(should_parse_imports_and_exports: "import \"lib/util.mint\" as U; export X, Y; let X = U.Inc(1); X" => Top { imports, exports, lets, expr } => {
+        assert_eq!( imports.len(), 1 );
+        assert_eq!( imports[0].path, "lib/util.mint" );
+        assert_eq!( imports[0].name, "U" );
+        assert_eq!( exports, Some(vec!["X".to_string(), "Y".to_string()]) );
+        assert_eq!( lets.len(), 1 );
+        assert!( matches!( &lets[0].expr, Expr::Call(_, f, _) if matches!( **f, Expr::Literal(Lit::Variable(ref x)) if x == "U.Inc" ) ) );
+        assert!( expr.is_some() );
+    });
+
+    test_parse!(should_parse_let_annotation: "let X : Map<String, List<a>> = 1;" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        let params = match &lets[0].ann {
+            Some(TypeAnn::Named(name, params)) if name == "Map" => params,
+            _ => panic!("expected Map annotation"),
+        };
+        assert!( matches!( &params[0], TypeAnn::Named(name, ps) if name == "String" && ps.is_empty() ) );
+        assert!( matches!( &params[1], TypeAnn::Named(name, ps) if name == "List" && matches!( &ps[0], TypeAnn::Var(v) if v == "a" ) ) );
+    });
+
+    test_parse!(should_parse_lambda_annotations: "fun(A : List<Number>, B) : fun({Int, b}) : b { A }" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let lambda = match expr {
+            Some(Expr::Literal(Lit::Lambda(lambda))) => lambda,
+            _ => panic!("expected lambda"),
+        };
+        assert_eq!( lambda.params.len(), 2 );
+        assert!( matches!( &lambda.param_anns[0], Some(TypeAnn::Named(name, _)) if name == "List" ) );
+        assert!( lambda.param_anns[1].is_none() );
+        assert!( matches!( &lambda.ret_ann, Some(TypeAnn::Fun(ps, _)) if matches!( &ps[0], TypeAnn::Tuple(ts) if ts.len() == 2 ) ) );
+    });
+
+    test_parse!(should_parse_contracts: "let F = fun(X) requires is_empty(X) requires X == [] ensures R -> R == 0 { length(X) };" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 1 );
+        assert!( expr.is_none() );
+        let lambda = match &lets[0].expr {
+            Expr::Literal(Lit::Lambda(lambda)) => lambda,
+            _ => panic!("expected lambda"),
+        };
+        assert_eq!( lambda.name, Some("F".to_string()) );
+        assert_eq!( lambda.requires.len(), 2 );
+        assert_eq!( lambda.requires[1].to_string(), "X == []" );
+        assert_eq!( lambda.ensures.len(), 1 );
+        assert_eq!( lambda.ensures[0].0, "R" );
+        assert_eq!( lambda.ensures[0].1.to_string(), "R == 0" );
+        assert!( lambda.body.expr.is_some() );
+    });
+
+    test_parse!(should_parse_try_catch: "try { let X = f(1); X } catch {oops, Y} => Y catch _ => 0" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let (block, handlers) = match expr {
+            Some(Expr::Try(_, block, handlers)) => (block, handlers),
+            _ => panic!("expected try"),
+        };
+        assert_eq!( block.lets.len(), 1 );
+        assert!( block.expr.is_some() );
+        assert_eq!( handlers.len(), 2 );
+        assert!( matches!( &handlers[0].0, Pat::Tuple(ps) if ps.len() == 2 ) );
+        assert!( matches!( &handlers[1], (Pat::Wild, Expr::Literal(Lit::Int(0))) ) );
+    });
+
+    test_parse!(should_parse_propagate: "fun() { let X = f(Y)?.g; X }" => Top { lets, expr, .. } => {
+        assert_eq!( lets.len(), 0 );
+        let lambda = match expr {
+            Some(Expr::Literal(Lit::Lambda(lambda))) => lambda,
+            _ => panic!("expected lambda"),
+        };
+        let (receiver, name) = match &lambda.body.lets[0].expr {
+            Expr::Method(_, receiver, name, _) => (receiver, name),
+            _ => panic!("expected method call"),
+        };
+        assert_eq!( name, "g" );
+        assert!( matches!( **receiver, Expr::Propagate(_, ref e) if matches!( **e, Expr::Call(_, _, _) ) ) );
+    });
+
+    test_parse!(should_keep_positions_of_expressions: "f(X).g? == Y" => Top { expr, .. } => {
+        let params = match expr {
+            Some(Expr::Call(8, _, params)) => params,
+            x => panic!("expected call at 8 but found {:?}", x),
+        };
+        assert!( matches!( &params[0], Expr::Propagate(6, e) if matches!( **e, Expr::Method(4, ref r, _, _) if matches!( **r, Expr::Call(1, _, _) ) ) ) );
+    });
+
+    #[test]
+    fn should_report_bad_interpolation_at_its_token() {
+        use super::super::tokenizer::tokenize;
+
+        for (input, position) in [("let X = \"a ${} b\";", 3), ("\"ab ${)} ${1}\"", 0), ("f(1, \"${1} ${f(} c\")", 4), ("f(\"${\"${)}\"}\")", 2)] {
+            let tokens = tokenize(input).unwrap();
+
+            assert!( matches!( parse(&tokens), Err(MatchError::Fatal(p)) if p == position ), "{}", input );
+        }
+    }
 }
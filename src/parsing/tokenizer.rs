@@ -1,35 +1,51 @@
 use motif::{alt, group, pred, seq, cases};
 use motif::MatchError;
-use crate::ast::{Token, TMeta};
+use crate::ast::{Token, TMeta, StringPart};
+use super::error::TokenizeError;
+use crate::bignum::{BigInt, Rational};
 
-pub fn tokenize( input : &str ) -> Result<Vec<Token>, MatchError> {
+pub fn tokenize( input : &str ) -> Result<Vec<Token>, TokenizeError> {
     match internal_tokenize(input) {
-        Ok(ts) => {
-            Ok(ts.into_iter()
-                 .filter(|t| matches!(t, I::T(_)))
-                 .map(|t| match t {
-                     I::T(x) => x,
-                     _ => panic!("Encountered Junk after filter")
-                 })
-                 .collect())
-        },
+        Ok(ts) => Ok(only_tokens(ts)),
         Err(e) => Err(e),
     }
 }
 
+fn only_tokens( input : Vec<I> ) -> Vec<Token> {
+    input.into_iter()
+         .filter(|t| matches!(t, I::T(_)))
+         .map(|t| match t {
+             I::T(x) => x,
+             _ => panic!("Encountered Junk after filter")
+         })
+         .collect()
+}
+
 #[derive(Debug)]
 enum I {
     T(Token),
     Junk,
+    Error(TokenizeError),
+}
+
+fn internal_tokenize( input : &str ) -> Result<Vec<I>, TokenizeError> {
+    tokenize_chars(input.char_indices().collect())
 }
 
-fn internal_tokenize( input : &str ) -> Result<Vec<I>, MatchError> {
-    let mut x = input.char_indices().enumerate();
+// NOTE:  Takes chars along with their positions so that the expressions embedded in interpolated strings keep their
+// positions in the whole input.
+fn tokenize_chars( input : Vec<(usize, char)> ) -> Result<Vec<I>, TokenizeError> {
+    let mut x = input.into_iter().enumerate();
 
+    // NOTE:  Comments are tokenized first so that anything following a # is always a comment, which is why maps
+    // open with %{ instead of #{.
     alt!( token: (usize, char) => I = junk 
+                                    | map_open
+                                    | raw_string
                                     | lower_symbol 
                                     | upper_symbol
                                     | string
+                                    | char_literal
                                     | number
                                     | punctuation
                                     );
@@ -37,9 +53,10 @@ fn internal_tokenize( input : &str ) -> Result<Vec<I>, MatchError> {
     let mut ret = vec![];
     loop {
         match token(&mut x) {
+            Ok(I::Error(e)) => return Err(e),
             Ok(t) => ret.push(t),
             Err(MatchError::ErrorEndOfFile) => break,
-            Err(e) => return Err(e),
+            Err(e) => return Err(TokenizeError::Match(e)),
         }
     }
 
@@ -59,6 +76,14 @@ group!(junk: (usize, char) => I = |input| {
     main(input)
 });
 
+group!(map_open: (usize, char) => I = |input| {
+    seq!(main: (usize, char) => I = _1 <= (_, '%'), _2 <= (_, '{'), {
+        I::T(Token::PercentLCurl(TMeta { start: _1.0, end: _2.0 }))
+    });
+
+    main(input)
+});
+
 group!(lower_symbol: (usize, char) => I = |input| {
     pred!(init_lower_symbol_char: (usize, char) = |c| c.1.is_lowercase() || c.1 == '_');
     pred!(rest_lower_symbol_char: (usize, char) = |c| c.1.is_alphanumeric() || c.1 == '_');
@@ -93,25 +118,238 @@ group!(upper_symbol: (usize, char) => I = |input| {
     main(input)
 });
 
+// NOTE:  Escapes shared by strings and chars.  Invalid escapes are returned as errors (instead of failing) so that
+// they can be reported with their position.
+group!(escape: (usize, char) => Result<char, TokenizeError> = |input| {
+    type R = Result<char, TokenizeError>;
+
+    seq!(n: (usize, char) => R = (_, 'n'), { Ok('\n') });
+    seq!(r: (usize, char) => R = (_, 'r'), { Ok('\r') });
+    seq!(t: (usize, char) => R = (_, 't'), { Ok('\t') });
+    seq!(slash: (usize, char) => R = (_, '\\'), { Ok('\\') });
+    seq!(zero: (usize, char) => R = (_, '0'), { Ok('\0') });
+    seq!(quote: (usize, char) => R = (_, '"'), { Ok('"') });
+    seq!(single_quote: (usize, char) => R = (_, '\''), { Ok('\'') });
+    seq!(dollar: (usize, char) => R = (_, '$'), { Ok('$') });
+
+    pred!(hex: (usize, char) = |c| c.1.is_ascii_hexdigit());
+
+    // NOTE:  The positions below are of the backslash, which is the byte before the escape code.
+    seq!(unicode: (usize, char) => R = u <= (_, 'u'), ! (_, '{'), d <= ! hex, ds <= * hex, ! (_, '}'), {
+        let value = format!("{}{}", d.1, ds.into_iter().map(|x| x.1).collect::<String>());
+        u32::from_str_radix(&value, 16).ok()
+                                       .and_then(char::from_u32)
+                                       .ok_or(TokenizeError::InvalidUnicodeEscape { position: u.0 - 1, value })
+    });
+
+    seq!(ascii: (usize, char) => R = x <= (_, 'x'), a <= ! hex, b <= ! hex, {
+        let value = format!("{}{}", a.1, b.1);
+        match u8::from_str_radix(&value, 16) {
+            Ok(v) if v <= 0x7F => Ok(v as char),
+            _ => Err(TokenizeError::InvalidHexEscape { position: x.0 - 1, value }),
+        }
+    });
+
+    pred!(unknown: (usize, char) => R = |_c| true => { Err(TokenizeError::UnknownEscape { position: _c.0 - 1, escape: _c.1 }) });
+
+    alt!(code: (usize, char) => R = n | r | t | slash | zero | quote | single_quote | dollar | unicode | ascii | unknown);
+    seq!(main: (usize, char) => R = (_, '\\'), c <= ! code, { c });
+
+    main(input)
+});
+
+// NOTE:  Strips the indentation of the closing """ from every line of a multi-line string.  Escaped chars are never
+// treated as newlines or indentation.
+fn strip_indent(cs : &[(char, bool)], position : usize) -> Result<String, TokenizeError> {
+    let mut lines = cs.split(|c| *c == ('\n', false)).collect::<Vec<_>>();
+    let indent = lines.pop().expect("split always returns at least one line");
+    if indent.iter().any(|c| c.1 || !c.0.is_whitespace()) {
+        return Err(TokenizeError::MultiLineStringClosingNotOnOwnLine { position });
+    }
+    let mut ret = vec![];
+    for (i, line) in lines.into_iter().enumerate() {
+        let line = match line.last() {
+            Some(('\r', false)) => &line[..line.len() - 1],
+            _ => line,
+        };
+        if line.iter().all(|c| !c.1 && c.0.is_whitespace()) {
+            ret.push(String::new());
+        }
+        else if line.starts_with(indent) {
+            ret.push(line[indent.len()..].iter().map(|c| c.0).collect());
+        }
+        else {
+            return Err(TokenizeError::MultiLineStringInsufficientIndent { position, line: i + 1 });
+        }
+    }
+    Ok(ret.join("\n"))
+}
+
+enum Piece {
+    Char(char),
+    Expr(usize, Vec<Token>),
+}
+
+// NOTE:  Strings without embedded expressions stay plain String tokens.
+fn string_token( meta : TMeta, pieces : Vec<Piece> ) -> I {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    for piece in pieces {
+        match piece {
+            Piece::Char(c) => literal.push(c),
+            Piece::Expr(position, ts) => {
+                if !literal.is_empty() {
+                    parts.push(StringPart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(StringPart::Expr(position, ts));
+            },
+        }
+    }
+    if parts.is_empty() {
+        return I::T(Token::String(meta, literal));
+    }
+    if !literal.is_empty() {
+        parts.push(StringPart::Literal(literal));
+    }
+    I::T(Token::InterpolatedString(meta, parts))
+}
+
+// NOTE:  The chars of an expression embedded in a string.  Nested braces and strings are included as a whole so that a
+// } inside of them does not end the expression.
+group!(embedded: (usize, char) => Vec<(usize, char)> = |input| {
+    type V = Vec<(usize, char)>;
+
+    pred!(other: (usize, char) => V = |c| c.1 != '{' && c.1 != '}' && c.1 != '"' => { vec![(c.0, c.1)] });
+    pred!(any: (usize, char) = |_c| true);
+
+    pred!(str_other: (usize, char) => V = |c| c.1 != '"' && c.1 != '\\' => { vec![(c.0, c.1)] });
+    seq!(str_escape: (usize, char) => V = s <= (_, '\\'), c <= ! any, { vec![s, c] });
+    alt!(str_item: (usize, char) => V = str_escape | str_other);
+    seq!(string: (usize, char) => V = s <= (_, '"'), items <= * str_item, e <= ! (_, '"'), {
+        [vec![s], items.concat(), vec![e]].concat()
+    });
+
+    seq!(nested: (usize, char) => V = s <= (_, '{'), items <= * embedded, e <= ! (_, '}'), {
+        [vec![s], items.concat(), vec![e]].concat()
+    });
+
+    alt!(main: (usize, char) => V = nested | string | other);
+
+    main(input)
+});
+
 group!(string: (usize, char) => I = |input| {
-    seq!(n: (usize, char) => char = (_, 'n'), { '\n' });
-    seq!(r: (usize, char) => char = (_, 'r'), { '\r' });
-    seq!(t: (usize, char) => char = (_, 't'), { '\t' });
-    seq!(slash: (usize, char) => char = (_, '\\'), { '\\' });
-    seq!(zero: (usize, char) => char = (_, '0'), { '\0' });
-    seq!(quote: (usize, char) => char = (_, '"'), { '"' });
-
-    alt!(code: (usize, char) => char = n | r | t | slash | zero | quote);
-    seq!(escape: (usize, char) => char = slash, c <= ! code, { c });
-
-    pred!(any: (usize, char) => char = |c| c.1 != '"' => { c.1 });
-    alt!(str_char: (usize, char) => char = escape
-                                         | any  
-                                         );
-
-    seq!(main: (usize, char) => I = _1 <= (_, '"'), sc <= * str_char, _2 <= (_, '"'), {
+    type C = Result<(char, bool), TokenizeError>;
+    type P = Result<Piece, TokenizeError>;
+
+    seq!(escaped_piece: (usize, char) => P = c <= escape, { c.map(Piece::Char) });
+    seq!(interpolation: (usize, char) => P = d <= (_, '$'), (_, '{'), items <= * embedded, ! (_, '}'), {
+        tokenize_chars(items.concat()).map(|ts| Piece::Expr(d.0, only_tokens(ts)))
+    });
+    pred!(any: (usize, char) => P = |c| c.1 != '"' => { Ok(Piece::Char(c.1)) });
+    alt!(str_char: (usize, char) => P = escaped_piece
+                                      | interpolation
+                                      | any  
+                                      );
+
+    // NOTE:  Only single line strings support interpolation.  A literal ${ can be written as \${.
+    seq!(single_line: (usize, char) => I = _1 <= (_, '"'), sc <= * str_char, _2 <= (_, '"'), {
+        let meta = TMeta { start: _1.0, end: _2.0 };
+        match sc.into_iter().collect::<Result<Vec<_>, _>>() {
+            Ok(pieces) => string_token(meta, pieces),
+            Err(e) => I::Error(e),
+        }
+    });
+
+    seq!(escaped: (usize, char) => C = c <= escape, { c.map(|c| (c, true)) });
+    pred!(plain: (usize, char) => C = |c| c.1 != '"' && c.1 != '\\' => { Ok((c.1, false)) });
+    alt!(block_char: (usize, char) => C = escaped | plain);
+
+    seq!(block_single: (usize, char) => Vec<C> = c <= block_char, { vec![c] });
+
+    // NOTE:  One or two quotes are allowed inside of a multi-line string as long as they are not followed by another quote.
+    seq!(block_quotes: (usize, char) => Vec<C> = (_, '"'), second <= ? (_, '"'), c <= block_char, {
+        let mut ret = vec![Ok(('"', false))];
+        if second.is_some() {
+            ret.push(Ok(('"', false)));
+        }
+        ret.push(c);
+        ret
+    });
+
+    alt!(block_item: (usize, char) => Vec<C> = block_single | block_quotes);
+
+    seq!(multi_line: (usize, char) => I = _1 <= (_, '"')
+                                        , (_, '"')
+                                        , (_, '"')
+                                        , ? (_, '\r')
+                                        , ! (_, '\n')
+                                        , items <= * block_item
+                                        , ! (_, '"')
+                                        , ! (_, '"')
+                                        , _2 <= ! (_, '"')
+                                        , {
+        let meta = TMeta { start: _1.0, end: _2.0 };
+        match items.into_iter().flatten().collect::<Result<Vec<_>, _>>() {
+            Ok(cs) => match strip_indent(&cs, _1.0) {
+                Ok(s) => I::T(Token::String(meta, s)),
+                Err(e) => I::Error(e),
+            },
+            Err(e) => I::Error(e),
+        }
+    });
+
+    alt!(main: (usize, char) => I = multi_line | single_line);
+
+    main(input)
+});
+
+// NOTE:  Raw strings have no escapes.  In r#"..."# quotes are allowed as long as they are not followed by #.
+group!(raw_string: (usize, char) => I = |input| {
+    pred!(not_quote: (usize, char) => char = |c| c.1 != '"' => { c.1 });
+    pred!(not_quote_or_hash: (usize, char) => char = |c| c.1 != '"' && c.1 != '#' => { c.1 });
+
+    seq!(plain: (usize, char) => I = _1 <= (_, 'r'), (_, '"'), cs <= * not_quote, _2 <= ! (_, '"'), {
         let meta = TMeta { start: _1.0, end: _2.0 };
-        I::T(Token::String(meta, sc.into_iter().collect::<String>()))
+        I::T(Token::String(meta, cs.into_iter().collect::<String>()))
+    });
+
+    seq!(single: (usize, char) => String = c <= not_quote, { c.to_string() });
+    seq!(quoted: (usize, char) => String = (_, '"'), qs <= * (_, '"'), c <= not_quote_or_hash, {
+        format!("\"{}{}", "\"".repeat(qs.len()), c)
+    });
+    alt!(hashed_item: (usize, char) => String = single | quoted);
+
+    // NOTE:  Quotes right before the closing "# are part of the string.
+    seq!(hashed: (usize, char) => I = _1 <= (_, 'r')
+                                    , (_, '#')
+                                    , (_, '"')
+                                    , items <= * hashed_item
+                                    , ! (_, '"')
+                                    , qs <= * (_, '"')
+                                    , _2 <= ! (_, '#')
+                                    , {
+        let meta = TMeta { start: _1.0, end: _2.0 };
+        I::T(Token::String(meta, format!("{}{}", items.concat(), "\"".repeat(qs.len()))))
+    });
+
+    alt!(main: (usize, char) => I = hashed | plain);
+
+    main(input)
+});
+
+group!(char_literal: (usize, char) => I = |input| {
+    pred!(any: (usize, char) => Result<char, TokenizeError> = |c| c.1 != '\'' && c.1 != '\\' && c.1 != '\n' => { Ok(c.1) });
+    alt!(single_char: (usize, char) => Result<char, TokenizeError> = escape
+                                                                    | any
+                                                                    );
+
+    seq!(main: (usize, char) => I = _1 <= (_, '\''), c <= ! single_char, _2 <= ! (_, '\''), {
+        let meta = TMeta { start: _1.0, end: _2.0 };
+        match c {
+            Ok(c) => I::T(Token::Char(meta, c)),
+            Err(e) => I::Error(e),
+        }
     });
 
     main(input)
@@ -127,7 +365,19 @@ group!(number: (usize, char) => I = |input| {
 
     pred!(digit: (usize, char) = |c| c.1.is_digit(10));
 
-    seq!(decimal: (usize, char) => (usize, String) = (_, '.'), d <= ! digit, ds <= * digit, {
+    // NOTE:  The digit after the dot is not fatal so that ranges like 0..10 tokenize as Int, DotDot, Int.
+    seq!(decimal: (usize, char) => (usize, String) = (_, '.'), d <= digit, ds <= * digit, {
+        let end = match ds.last() {
+            Some(x) => x.0,
+            None => d.0,
+        };
+        (end, format!("{}{}", d.1, ds.into_iter().map(|x| x.1).collect::<String>()))
+    });
+
+    pred!(non_zero_digit: (usize, char) = |c| c.1.is_digit(10) && c.1 != '0');
+
+    // NOTE:  Leading zeros are skipped and the first remaining digit is fatal so that a zero denominator is an error.
+    seq!(denominator: (usize, char) => (usize, String) = (_, '/'), * (_, '0'), d <= ! non_zero_digit, ds <= * digit, {
         let end = match ds.last() {
             Some(x) => x.0,
             None => d.0,
@@ -150,6 +400,51 @@ group!(number: (usize, char) => I = |input| {
                      , ds.into_iter().map(|x| x.1).collect::<String>()))
     });
 
+    // NOTE:  Exact numbers have a suffix.  Integers ending in n are BigInts.  Numbers ending in r are Rationals and can
+    // have a decimal and a denominator (like 1.25r or -1/3r).
+    seq!(bigint: (usize, char) => I = sign <= ? (_, '+') | (_, '-')
+                                    , d <= digit
+                                    , ds <= * digit
+                                    , suffix <= (_, 'n'), {
+        let start = match sign {
+            Some(x) => x.0,
+            None => d.0,
+        };
+        let meta = TMeta { start, end: suffix.0 };
+        let n = format!("{}{}{}", m(sign), d.1, ds.into_iter().map(|x| x.1).collect::<String>());
+        I::T(Token::BigInt(meta, BigInt::parse(&n).expect("allowed bigint string that fails to parse")))
+    });
+
+    seq!(rational: (usize, char) => I = sign <= ? (_, '+') | (_, '-')
+                                      , d <= digit
+                                      , ds <= * digit
+                                      , maybe_decimal <= ? decimal
+                                      , maybe_denominator <= ? denominator
+                                      , suffix <= (_, 'r'), {
+        let start = match sign {
+            Some(x) => x.0,
+            None => d.0,
+        };
+        let meta = TMeta { start, end: suffix.0 };
+        let dot = match maybe_decimal {
+            Some(_) => ".",
+            None => "",
+        };
+        let slash = match maybe_denominator {
+            Some(_) => "/",
+            None => "",
+        };
+        let n = format!("{}{}{}{}{}{}{}"
+                       , m(sign)
+                       , d.1
+                       , ds.into_iter().map(|x| x.1).collect::<String>()
+                       , dot
+                       , m(maybe_decimal)
+                       , slash
+                       , m(maybe_denominator));
+        I::T(Token::Rational(meta, Rational::parse(&n).expect("allowed rational string that fails to parse")))
+    });
+
     seq!(main: (usize, char) => I = sign <= ? (_, '+') | (_, '-')
                                   , d <= digit
                                   , ds <= * digit
@@ -176,6 +471,15 @@ group!(number: (usize, char) => I = |input| {
             ret
         };
         let meta = TMeta { start, end };
+        // NOTE:  Without a decimal or an exponent the number is an integer.
+        if maybe_decimal.is_none() && maybe_sci_not.is_none() {
+            let n = format!("{}{}{}", m(sign), d.1, ds.iter().map(|x| x.1).collect::<String>());
+            // NOTE:  Integer literals that do not fit into an i64 become BigInts instead of losing precision.
+            match n.parse::<i64>() {
+                Ok(x) => { return I::T(Token::Int(meta, x)); },
+                Err(_) => { return I::T(Token::BigInt(meta, BigInt::parse(&n).expect("digits that fail to parse as a bigint"))); },
+            }
+        }
         let dot = match maybe_decimal {
             Some(_) => ".",
             None => "",
@@ -191,7 +495,9 @@ group!(number: (usize, char) => I = |input| {
         I::T(Token::Number(meta, ret))
     });
 
-    main(input)
+    alt!(exact_or_main: (usize, char) => I = bigint | rational | main);
+
+    exact_or_main(input)
 });
 
 group!(punctuation: (usize, char) => I = |input| {
@@ -213,6 +519,8 @@ group!(punctuation: (usize, char) => I = |input| {
     seq!(semicolon: (usize, char) => I = p <= (_, ';'), { I::T(Token::Semicolon(m(p))) });
     seq!(or_bar: (usize, char) => I = p <= (_, '|'), { I::T(Token::OrBar(m(p))) });
     seq!(at: (usize, char) => I = p <= (_, '@'), { I::T(Token::At(m(p))) });
+    seq!(caret: (usize, char) => I = p <= (_, '^'), { I::T(Token::Caret(m(p))) });
+    seq!(question: (usize, char) => I = p <= (_, '?'), { I::T(Token::Question(m(p))) });
 
     alt!(single: (usize, char) => I = l_paren
                                     | r_paren
@@ -229,6 +537,8 @@ group!(punctuation: (usize, char) => I = |input| {
                                     | semicolon
                                     | or_bar
                                     | at
+                                    | caret
+                                    | question
                                     );
 
     seq!(single_left_arrow: (usize, char) => I = _1 <= (_, '<'), _2 <= (_, '-'), {
@@ -243,10 +553,26 @@ group!(punctuation: (usize, char) => I = |input| {
     seq!(double_right_arrow: (usize, char) => I = _1 <= (_, '='), _2 <= (_, '>'), {
         I::T(Token::DRArrow(TMeta { start: _1.0, end: _2.0 }))
     });
+    seq!(double_equal: (usize, char) => I = _1 <= (_, '='), _2 <= (_, '='), {
+        I::T(Token::DoubleEqual(TMeta { start: _1.0, end: _2.0 }))
+    });
+    seq!(plus_plus: (usize, char) => I = _1 <= (_, '+'), _2 <= (_, '+'), {
+        I::T(Token::PlusPlus(TMeta { start: _1.0, end: _2.0 }))
+    });
+    seq!(dot_dot_equal: (usize, char) => I = _1 <= (_, '.'), (_, '.'), _3 <= (_, '='), {
+        I::T(Token::DotDotEqual(TMeta { start: _1.0, end: _3.0 }))
+    });
+    seq!(dot_dot: (usize, char) => I = _1 <= (_, '.'), _2 <= (_, '.'), {
+        I::T(Token::DotDot(TMeta { start: _1.0, end: _2.0 }))
+    });
     alt!(main: (usize, char) => I = single_left_arrow
                                   | double_left_arrow
                                   | single_right_arrow
                                   | double_right_arrow
+                                  | double_equal
+                                  | dot_dot_equal
+                                  | dot_dot
+                                  | plus_plus
                                   | single );
 
     main(input)
@@ -258,7 +584,7 @@ mod test {
     use super::*;
 
     #[test]
-    fn should_parse_single_right_arrow() -> Result<(), MatchError> {
+    fn should_parse_single_right_arrow() -> Result<(), TokenizeError> {
         let input = r#"->"#;
         let output = internal_tokenize(input)?;
 
@@ -276,7 +602,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_right_angle() -> Result<(), MatchError> {
+    fn should_parse_right_angle() -> Result<(), TokenizeError> {
         let input = r#">"#;
         let output = internal_tokenize(input)?;
 
@@ -294,7 +620,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_left_angle() -> Result<(), MatchError> {
+    fn should_parse_left_angle() -> Result<(), TokenizeError> {
         let input = r#"<"#;
         let output = internal_tokenize(input)?;
 
@@ -312,7 +638,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_double_left_arrow() -> Result<(), MatchError> {
+    fn should_parse_double_left_arrow() -> Result<(), TokenizeError> {
         let input = r#"<="#;
         let output = internal_tokenize(input)?;
 
@@ -330,7 +656,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_single_left_arrow() -> Result<(), MatchError> {
+    fn should_parse_single_left_arrow() -> Result<(), TokenizeError> {
         let input = r#"<-"#;
         let output = internal_tokenize(input)?;
 
@@ -348,7 +674,147 @@ mod test {
     }
 
     #[test]
-    fn should_parse_comment() -> Result<(), MatchError> {
+    fn should_parse_dot_dot() -> Result<(), TokenizeError> {
+        let input = r#".."#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let (start, end) = match &output[0] {
+            I::T(Token::DotDot(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 0 );
+        assert_eq!( end, 1 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_dot_dot_equal() -> Result<(), TokenizeError> {
+        let input = r#"..="#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let (start, end) = match &output[0] {
+            I::T(Token::DotDotEqual(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 0 );
+        assert_eq!( end, 2 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_plus_plus() -> Result<(), TokenizeError> {
+        let input = r#"++"#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let (start, end) = match &output[0] {
+            I::T(Token::PlusPlus(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 0 );
+        assert_eq!( end, 1 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_number_range() -> Result<(), TokenizeError> {
+        let input = r#"200..299"#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 3 );
+
+        assert!( matches!( output[0], I::T(Token::Int(_, 200)) ) );
+        assert!( matches!( output[1], I::T(Token::DotDot(_)) ) );
+        assert!( matches!( output[2], I::T(Token::Int(_, 299)) ) );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_double_equal() -> Result<(), TokenizeError> {
+        let input = r#"=="#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let (start, end) = match &output[0] {
+            I::T(Token::DoubleEqual(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 0 );
+        assert_eq!( end, 1 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_caret() -> Result<(), TokenizeError> {
+        let input = r#"^"#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let (start, end) = match &output[0] {
+            I::T(Token::Caret(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 0 );
+        assert_eq!( end, 0 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_question() -> Result<(), TokenizeError> {
+        let input = r#"X?"#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 2 );
+
+        let (start, end) = match &output[1] {
+            I::T(Token::Question(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 1 );
+        assert_eq!( end, 1 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_percent_l_curl() -> Result<(), TokenizeError> {
+        let input = r#"%{"#;
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let (start, end) = match &output[0] {
+            I::T(Token::PercentLCurl(m)) => (m.start, m.end),
+            _ => panic!("not punctuation"),
+        };
+
+        assert_eq!( start, 0 );
+        assert_eq!( end, 1 );
+        
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_comment() -> Result<(), TokenizeError> {
         let input = r#"#this is a comment
         blah"#;
         let output = internal_tokenize(input)?;
@@ -361,7 +827,20 @@ mod test {
     }
 
     #[test]
-    fn should_parse_whitespace() -> Result<(), MatchError> {
+    fn should_parse_comment_starting_with_l_curl() -> Result<(), TokenizeError> {
+        let input = "#{ a => 1 }\nblah";
+        let output = internal_tokenize(input)?;
+
+        assert_eq!( output.len(), 2 );
+
+        assert!( matches!( output[0], I::Junk ) );
+        assert!( matches!( output[1], I::T(Token::LowerSymbol(_, ref x)) if x == "blah" ) );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_whitespace() -> Result<(), TokenizeError> {
         let input = "      \n\t\rfalse";
         let output = internal_tokenize(input)?;
 
@@ -375,7 +854,7 @@ mod test {
     macro_rules! string_test {
         ($name:ident: $input:expr => $expected:expr) => {
             #[test]
-            fn $name() -> Result<(), MatchError> {
+            fn $name() -> Result<(), TokenizeError> {
                 let output = internal_tokenize($input)?;
 
                 assert_eq!( output.len(), 1 );
@@ -400,11 +879,108 @@ mod test {
     string_test!(should_parser_string_with_slash_t: r#""string \t input""# => "string \t input");
     string_test!(should_parse_string_with_slash_slash: r#""string \\ input""# => "string \\ input");
     string_test!(should_parse_string_with_slash_quote: r#""string \" input""# => "string \" input");
+    string_test!(should_parse_string_with_unicode_escape: r#""caf\u{e9} \u{1F600}""# => "café 😀");
+
+    #[test]
+    fn should_reject_surrogate_unicode_escape() {
+        let output = internal_tokenize(r#""\u{D800}""#);
+
+        assert!( matches!( output, Err(TokenizeError::InvalidUnicodeEscape { position: 1, .. }) ) );
+    }
+
+    string_test!(should_parse_string_with_hex_escape: r#""\x41\x7f""# => "A\x7f");
+    string_test!(should_parse_raw_string: r#"r"C:\path\[a-z]+""# => r"C:\path\[a-z]+");
+    string_test!(should_parse_hashed_raw_string: r##"r#"say "hi" \n""#"## => r#"say "hi" \n""#);
+    string_test!(should_parse_multi_line_string: "\"\"\"\n    one\n      two \"quoted\"\n\n    three\\n\n    \"\"\"" => "one\n  two \"quoted\"\n\nthree\n");
+
+    #[test]
+    fn should_parse_interpolated_string() -> Result<(), TokenizeError> {
+        let output = internal_tokenize(r#""Hi ${name(%{a => "}"})}!\${x}""#)?;
+
+        assert_eq!( output.len(), 1 );
+
+        let parts = match &output[0] {
+            I::T(Token::InterpolatedString(_, parts)) => parts,
+            _ => panic!("not interpolated string"),
+        };
+
+        assert_eq!( parts.len(), 3 );
+        assert!( matches!( &parts[0], StringPart::Literal(x) if x == "Hi " ) );
+        assert!( matches!( &parts[1], StringPart::Expr(4, ts) if ts.len() == 8 ) );
+        assert!( matches!( &parts[1], StringPart::Expr(_, ts) if matches!( &ts[0], Token::LowerSymbol(m, _) if m.start == 6 ) ) );
+        assert!( matches!( &parts[2], StringPart::Literal(x) if x == "!${x}" ) );
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_unknown_escape_position() {
+        let output = internal_tokenize(r#"  "ab\q""#);
+
+        assert!( matches!( output, Err(TokenizeError::UnknownEscape { position: 5, escape: 'q' }) ) );
+    }
+
+    #[test]
+    fn should_reject_non_ascii_hex_escape() {
+        let output = internal_tokenize(r#""\x80""#);
+
+        assert!( matches!( output, Err(TokenizeError::InvalidHexEscape { position: 1, .. }) ) );
+    }
+
+    #[test]
+    fn should_reject_multi_line_string_with_insufficient_indent() {
+        let output = internal_tokenize("\"\"\"\n    one\n  two\n    \"\"\"");
+
+        assert!( matches!( output, Err(TokenizeError::MultiLineStringInsufficientIndent { position: 0, line: 2 }) ) );
+    }
+
+    #[test]
+    fn should_not_treat_raw_prefix_as_symbol() -> Result<(), TokenizeError> {
+        let output = internal_tokenize("rest r")?;
+
+        assert_eq!( output.len(), 3 );
+        assert!( matches!( &output[0], I::T(Token::LowerSymbol(_, x)) if x == "rest" ) );
+        assert!( matches!( &output[2], I::T(Token::LowerSymbol(_, x)) if x == "r" ) );
+        Ok(())
+    }
+
+    macro_rules! char_test {
+        ($name:ident: $input:expr => $expected:expr) => {
+            #[test]
+            fn $name() -> Result<(), TokenizeError> {
+                let output = internal_tokenize($input)?;
+
+                assert_eq!( output.len(), 1 );
+
+                let (start, end, value) = match &output[0] {
+                    I::T(Token::Char(m, c)) => (m.start, m.end, *c),
+                    _ => panic!("not char"),
+                };
+
+                assert_eq!( start, 0 );
+                assert_eq!( end, $input.len() - 1 );
+                assert_eq!( value, $expected );
+                Ok(())
+            }
+        };
+    }
+
+    char_test!(should_parse_char: "'a'" => 'a');
+    char_test!(should_parse_char_with_slash_n: r"'\n'" => '\n');
+    char_test!(should_parse_char_with_slash_single_quote: r"'\''" => '\'');
+    char_test!(should_parse_char_with_unicode_escape: r"'\u{1F600}'" => '😀');
+    char_test!(should_parse_double_quote_char: "'\"'" => '"');
+
+    #[test]
+    fn should_reject_empty_char() {
+        let output = internal_tokenize("''");
+
+        assert!( matches!( output, Err(TokenizeError::Match(MatchError::Fatal(_))) ) );
+    }
 
     macro_rules! number_test {
         ($name:ident: $input:expr => $expected:expr) => {
             #[test]
-            fn $name() -> Result<(), MatchError> {
+            fn $name() -> Result<(), TokenizeError> {
                 let output = internal_tokenize($input)?;
 
                 assert_eq!( output.len(), 1 );
@@ -422,10 +998,7 @@ mod test {
         };
     }
 
-    number_test!(should_parse_zero: "0" => 0.0);
     number_test!(should_parse_zero_point_zero: "0.0" => 0.0);
-    number_test!(should_parse_negative: "-1" => -1.0);
-    number_test!(should_parse_plus: "+1" => 1.0);
     number_test!(should_parse_sci_not_big_e: "1E1" => 1E1);
     number_test!(should_parse_sci_not_little_e: "1e1" => 1e1);
     number_test!(should_parse_plus_one: "+1.0" => 1.0);
@@ -436,10 +1009,71 @@ mod test {
     number_test!(should_parse_decimal_with_sci_not_neg_big_e: "1234.5678E-90" => 1234.5678E-90);
     number_test!(should_parse_decimal_with_sci_not_neg_little_e: "1234.5678e-90" => 1234.5678e-90);
     number_test!(should_parse_decimal_with_sci_not_neg_little_e_901: "1234.5678e-901" => 1234.5678e-901);
-    number_test!(should_parse_number: "1234" => 1234.0);
+
+    macro_rules! int_test {
+        ($name:ident: $input:expr => $expected:expr) => {
+            #[test]
+            fn $name() -> Result<(), TokenizeError> {
+                let output = internal_tokenize($input)?;
+
+                assert_eq!( output.len(), 1 );
+
+                let (start, end, value) = match &output[0] {
+                    I::T(Token::Int(m, n)) => (m.start, m.end, *n),
+                    _ => panic!("not int"),
+                };
+
+                assert_eq!( start, 0 );
+                assert_eq!( end, $input.len() - 1 );
+                assert_eq!( value, $expected );
+                Ok(())
+            }
+        };
+    }
+
+    int_test!(should_parse_zero: "0" => 0);
+    int_test!(should_parse_negative: "-1" => -1);
+    int_test!(should_parse_plus: "+1" => 1);
+    int_test!(should_parse_number: "1234" => 1234);
+    int_test!(should_parse_int_above_f64_precision: "9007199254740993" => 9007199254740993);
+
+    macro_rules! exact_test {
+        ($name:ident: $input:expr => $expected:expr) => {
+            #[test]
+            fn $name() -> Result<(), TokenizeError> {
+                let output = internal_tokenize($input)?;
+
+                assert_eq!( output.len(), 1 );
+
+                let (start, end, value) = match &output[0] {
+                    I::T(Token::BigInt(m, n)) => (m.start, m.end, format!("{}n", n)),
+                    I::T(Token::Rational(m, n)) => (m.start, m.end, format!("{}r", n)),
+                    _ => panic!("not exact number"),
+                };
+
+                assert_eq!( start, 0 );
+                assert_eq!( end, $input.len() - 1 );
+                assert_eq!( value, $expected );
+                Ok(())
+            }
+        };
+    }
+
+    exact_test!(should_parse_bigint: "-123456789012345678901234567890n" => "-123456789012345678901234567890n");
+    exact_test!(should_parse_int_too_big_for_i64: "9223372036854775808" => "9223372036854775808n");
+    exact_test!(should_parse_rational_decimal: "1.25r" => "5/4r");
+    exact_test!(should_parse_rational_fraction: "-2/6r" => "-1/3r");
+    exact_test!(should_parse_whole_rational: "+3r" => "3r");
+
+    #[test]
+    fn should_reject_zero_denominator() {
+        let output = internal_tokenize("1/00r");
+
+        assert!( matches!( output, Err(TokenizeError::Match(MatchError::Fatal(_))) ) );
+    }
 
     #[test]
-    fn should_parse_boolean_starting_lower_symbol() -> Result<(), MatchError> {
+    fn should_parse_boolean_starting_lower_symbol() -> Result<(), TokenizeError> {
         let input = "false_";
         let output = internal_tokenize(input)?;
 
@@ -458,7 +1092,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_lower_symbol() -> Result<(), MatchError> {
+    fn should_parse_lower_symbol() -> Result<(), TokenizeError> {
         let input = "lower_symbol";
         let output = internal_tokenize(input)?;
 
@@ -477,7 +1111,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_single_lower_symbol() -> Result<(), MatchError> {
+    fn should_parse_single_lower_symbol() -> Result<(), TokenizeError> {
         let input = "l";
         let output = internal_tokenize(input)?;
 
@@ -496,7 +1130,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_upper_symbol() -> Result<(), MatchError> {
+    fn should_parse_upper_symbol() -> Result<(), TokenizeError> {
         let input = "UpperSymbol";
         let output = internal_tokenize(input)?;
 
@@ -515,7 +1149,7 @@ mod test {
     }
 
     #[test]
-    fn should_parse_single_upper_symbol() -> Result<(), MatchError> {
+    fn should_parse_single_upper_symbol() -> Result<(), TokenizeError> {
         let input = "U";
         let output = internal_tokenize(input)?;
 
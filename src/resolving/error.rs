@@ -0,0 +1,20 @@
+
+#[derive(Debug)]
+pub enum ResolveError {
+    ModuleNotFound { import : String, searched : Vec<String> },
+    CannotReadManifest { path : String, reason : String },
+    InvalidManifest { path : String, line : usize, reason : String },
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ResolveError::ModuleNotFound { import, searched } => 
+                write!(f, "cannot find module {:?}, searched [{}]", import, searched.join(", ")),
+            ResolveError::CannotReadManifest { path, reason } => write!(f, "cannot read manifest {}: {}", path, reason),
+            ResolveError::InvalidManifest { path, line, reason } => write!(f, "invalid manifest {} at line {}: {}", path, line, reason),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
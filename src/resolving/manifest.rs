@@ -0,0 +1,200 @@
+
+use std::collections::HashMap;
+
+use super::error::ResolveError;
+
+// NOTE:  mint.toml only needs a small part of toml:
+//
+//  [package]
+//  src = ["src", "lib"]
+//
+//  [dependencies]
+//  util = { path = "../util" }
+//
+// Paths are relative to the directory that holds the manifest.  A missing src means the directory itself.
+#[derive(Debug, PartialEq)]
+pub struct Manifest {
+    pub src : Vec<String>,
+    pub dependencies : HashMap<String, String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    List(Vec<String>),
+    Table(Vec<(String, Value)>),
+}
+
+pub fn parse(path : &str, input : &str) -> Result<Manifest, ResolveError> {
+    let mut manifest = Manifest { src: vec![], dependencies: HashMap::new() };
+    let mut src = None;
+    let mut section = String::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |reason : &str| ResolveError::InvalidManifest { path: path.into(), line: line_number, reason: reason.into() };
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if !line.ends_with(']') {
+                return Err(error("expected ] at the end of section header"));
+            }
+            section = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => { return Err(error("expected key = value")); },
+        };
+        let value = parse_value(value).map_err(|reason| error(&reason))?;
+
+        match (section.as_str(), key, value) {
+            ("package", "src", Value::List(roots)) => { src = Some(roots); },
+            ("package", "src", Value::String(root)) => { src = Some(vec![root]); },
+            ("package", "src", _) => { return Err(error("src must be a string or a list of strings")); },
+            ("package", _, _) => { },
+            ("dependencies", name, Value::Table(fields)) => {
+                match fields.into_iter().find(|(k, _)| k == "path") {
+                    Some((_, Value::String(path))) => { manifest.dependencies.insert(name.into(), path); },
+                    _ => { return Err(error("dependencies need a local path like { path = \"../lib\" }")); },
+                }
+            },
+            ("dependencies", _, _) => { return Err(error("dependencies need a local path like { path = \"../lib\" }")); },
+            (s, _, _) => { return Err(error(&format!("unknown section [{}]", s))); },
+        }
+    }
+
+    manifest.src = src.unwrap_or_else(|| vec![".".into()]);
+    Ok(manifest)
+}
+
+fn strip_comment(line : &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => { in_string = !in_string; },
+            '#' if !in_string => { return &line[..i]; },
+            _ => { },
+        }
+    }
+    line
+}
+
+fn parse_value(input : &str) -> Result<Value, String> {
+    let (value, rest) = value(input)?;
+    if rest.trim().is_empty() {
+        Ok(value)
+    }
+    else {
+        Err(format!("unexpected {:?} after value", rest.trim()))
+    }
+}
+
+fn value(input : &str) -> Result<(Value, &str), String> {
+    let input = input.trim_start();
+    if input.starts_with('"') {
+        let (s, rest) = string(input)?;
+        Ok((Value::String(s), rest))
+    }
+    else if let Some(rest) = input.strip_prefix('[') {
+        let (items, rest) = items(rest, ']', |input| string(input.trim_start()))?;
+        Ok((Value::List(items), rest))
+    }
+    else if let Some(rest) = input.strip_prefix('{') {
+        let (fields, rest) = items(rest, '}', |input| {
+            let (key, rest) = match input.split_once('=') {
+                Some((key, rest)) => (key.trim().to_string(), rest),
+                None => { return Err("expected key = value in table".to_string()); },
+            };
+            let (value, rest) = value(rest)?;
+            Ok(((key, value), rest))
+        })?;
+        Ok((Value::Table(fields), rest))
+    }
+    else {
+        Err(format!("expected a string, list, or table but found {:?}", input))
+    }
+}
+
+fn items<T>(input : &str, close : char, item : impl Fn(&str) -> Result<(T, &str), String>) -> Result<(Vec<T>, &str), String> {
+    let mut items = vec![];
+    let mut input = input.trim_start();
+    loop {
+        if let Some(rest) = input.strip_prefix(close) {
+            return Ok((items, rest));
+        }
+        let (x, rest) = item(input)?;
+        items.push(x);
+        input = rest.trim_start();
+        if let Some(rest) = input.strip_prefix(',') {
+            input = rest.trim_start();
+        }
+        else if !input.starts_with(close) {
+            return Err(format!("expected , or {}", close));
+        }
+    }
+}
+
+// NOTE:  Only the escapes that are likely to show up in a path are supported.
+fn string(input : &str) -> Result<(String, &str), String> {
+    let mut chars = match input.strip_prefix('"') {
+        Some(rest) => rest.char_indices(),
+        None => { return Err("expected a string".into()); },
+    };
+    let mut ret = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => { return Ok((ret, &input[i + 2..])); },
+            '\\' => match chars.next() {
+                Some((_, '\\')) => ret.push('\\'),
+                Some((_, '"')) => ret.push('"'),
+                _ => { return Err("unsupported escape in string".into()); },
+            },
+            c => ret.push(c),
+        }
+    }
+    Err("unterminated string".into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_manifest() {
+        let input = r#"
+# a project
+[package]
+name = "app"
+src = ["src", "lib"] # roots
+
+[dependencies]
+util = { path = "../util" }
+"#;
+
+        let output = parse("mint.toml", input).unwrap();
+
+        assert_eq!( output.src, vec!["src".to_string(), "lib".to_string()] );
+        assert_eq!( output.dependencies.len(), 1 );
+        assert_eq!( output.dependencies["util"], "../util" );
+    }
+
+    #[test]
+    fn should_default_src_to_manifest_directory() {
+        let output = parse("mint.toml", "[package]\nname = \"app\"").unwrap();
+
+        assert_eq!( output.src, vec![".".to_string()] );
+    }
+
+    #[test]
+    fn should_reject_non_path_dependency() {
+        let output = parse("mint.toml", "[dependencies]\nutil = \"1.0\"");
+
+        assert!( matches!( output, Err(ResolveError::InvalidManifest { line: 2, .. }) ) );
+    }
+}
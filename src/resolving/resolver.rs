@@ -0,0 +1,255 @@
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::error::ResolveError;
+use super::manifest;
+
+pub const MANIFEST : &str = "mint.toml";
+pub const SEARCH_PATH_VAR : &str = "MINT_PATH";
+
+// NOTE:  A directory with a mint.toml (or a dependency without one).  dependencies maps the names that the package
+// declares to other packages by index, so each package only sees its own dependencies.
+#[derive(Debug)]
+struct Package {
+    dir : PathBuf,
+    roots : Vec<PathBuf>,
+    dependencies : HashMap<String, usize>,
+}
+
+// NOTE:  Finds the file for an import.  Only local directories are ever searched.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    packages : Vec<Package>,
+    search_path : Vec<PathBuf>,
+}
+
+impl Resolver {
+    // NOTE:  A resolver that only finds imports relative to the importing file.
+    pub fn new() -> Self {
+        Resolver::default()
+    }
+
+    // NOTE:  Uses the closest mint.toml in dir or its ancestors along with the directories in MINT_PATH.
+    pub fn discover(dir : &Path) -> Result<Self, ResolveError> {
+        let mut resolver = Resolver::new();
+        if let Some(manifest) = dir.ancestors().map(|d| d.join(MANIFEST)).find(|p| p.is_file()) {
+            resolver.add_manifest(&manifest)?;
+        }
+        if let Some(paths) = std::env::var_os(SEARCH_PATH_VAR) {
+            for path in std::env::split_paths(&paths).filter(|p| !p.as_os_str().is_empty()) {
+                resolver.add_search_path(path);
+            }
+        }
+        Ok(resolver)
+    }
+
+    pub fn add_manifest(&mut self, path : &Path) -> Result<(), ResolveError> {
+        self.read_manifest(path)?;
+        Ok(())
+    }
+
+    pub fn add_search_path<P : Into<PathBuf>>(&mut self, dir : P) {
+        self.search_path.push(dir.into());
+    }
+
+    // NOTE:  Imports are looked for relative to the importing file first.  After that, an import whose first
+    // component names a dependency of the importing file's package is looked for under that dependency, then under
+    // the package's source roots, and finally under each MINT_PATH directory in order.  A file outside of every
+    // package belongs to the first package that was added.
+    pub fn resolve(&self, import : &str, from : Option<&Path>) -> Result<PathBuf, ResolveError> {
+        let import_path = Path::new(import);
+        let relative_dir = from.and_then(|f| f.parent()).map(Path::to_path_buf).unwrap_or_default();
+        let mut candidates = vec![relative_dir.join(import_path)];
+
+        if !import_path.is_absolute() {
+            if let Some(package) = self.package_of(from) {
+                let mut components = import_path.components();
+                if let Some(Component::Normal(first)) = components.next() {
+                    if let Some(dependency) = first.to_str().and_then(|name| package.dependencies.get(name)) {
+                        let rest = components.as_path();
+                        candidates.extend(self.packages[*dependency].roots.iter().map(|r| r.join(rest)));
+                    }
+                }
+                candidates.extend(package.roots.iter().map(|r| r.join(import_path)));
+            }
+            candidates.extend(self.search_path.iter().map(|r| r.join(import_path)));
+        }
+
+        match candidates.iter().find(|c| c.is_file()) {
+            Some(c) => Ok(c.clone()),
+            None => Err(ResolveError::ModuleNotFound { import: import.into()
+                                                     , searched: candidates.iter().map(|c| c.display().to_string()).collect()
+                                                     }),
+        }
+    }
+
+    // NOTE:  The innermost package whose directory holds the file, so a dependency nested inside of another package
+    // still owns its own files.
+    fn package_of(&self, file : Option<&Path>) -> Option<&Package> {
+        let file = match file {
+            Some(file) => canonical(file),
+            None => { return self.packages.first(); },
+        };
+        self.packages.iter()
+                     .filter(|p| file.starts_with(&p.dir))
+                     .max_by_key(|p| p.dir.components().count())
+                     .or(self.packages.first())
+    }
+
+    // NOTE:  Adds the package for the manifest along with the packages of its dependencies and returns its index.
+    // Each directory is only read once, which also stops dependency cycles.
+    fn read_manifest(&mut self, path : &Path) -> Result<usize, ResolveError> {
+        let name = path.display().to_string();
+        let dir = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        if let Some(index) = self.find_package(&dir) {
+            return Ok(index);
+        }
+        let input = std::fs::read_to_string(path).map_err(|e| ResolveError::CannotReadManifest { path: name.clone(), reason: e.to_string() })?;
+        let manifest = manifest::parse(&name, &input)?;
+
+        let index = self.packages.len();
+        self.packages.push(Package { dir: canonical(&dir), roots: manifest.src.iter().map(|s| dir.join(s)).collect(), dependencies: HashMap::new() });
+
+        let mut dependencies = manifest.dependencies.into_iter().collect::<Vec<_>>();
+        dependencies.sort();
+        for (dep_name, dep_path) in dependencies {
+            let dep_dir = dir.join(dep_path);
+            let dep_manifest = dep_dir.join(MANIFEST);
+            let dependency = if dep_manifest.is_file() {
+                self.read_manifest(&dep_manifest)?
+            }
+            else {
+                match self.find_package(&dep_dir) {
+                    Some(index) => index,
+                    None => {
+                        self.packages.push(Package { dir: canonical(&dep_dir), roots: vec![dep_dir], dependencies: HashMap::new() });
+                        self.packages.len() - 1
+                    },
+                }
+            };
+            self.packages[index].dependencies.insert(dep_name, dependency);
+        }
+
+        Ok(index)
+    }
+
+    fn find_package(&self, dir : &Path) -> Option<usize> {
+        let dir = canonical(dir);
+        self.packages.iter().position(|p| p.dir == dir)
+    }
+}
+
+// NOTE:  Files that don't exist yet are canonicalized through their closest existing ancestor.
+fn canonical(path : &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(dir) = ancestor.canonicalize() {
+            return dir.join(path.strip_prefix(ancestor).expect("ancestor is a prefix"));
+        }
+    }
+    path.to_path_buf()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn dir(name : &str, files : &[(&str, &str)]) -> TempDir {
+        TempDir::new(&format!("resolver_{}", name), files)
+    }
+
+    #[test]
+    fn should_prefer_file_relative_import() {
+        let dir = dir("relative", &[ ("app/main.mint", "")
+                                   , ("app/util.mint", "")
+                                   , ("lib/util.mint", "")
+                                   ]);
+        let mut resolver = Resolver::new();
+        resolver.add_search_path(dir.join("lib"));
+
+        let output = resolver.resolve("util.mint", Some(&dir.join("app/main.mint"))).unwrap();
+
+        assert_eq!( output, dir.join("app/util.mint") );
+    }
+
+    #[test]
+    fn should_resolve_from_source_roots_and_dependencies() {
+        let dir = dir("manifest", &[ ("app/mint.toml", "[package]\nsrc = [\"src\"]\n[dependencies]\nutil = { path = \"../util\" }")
+                                   , ("app/src/main.mint", "")
+                                   , ("app/src/shared/list.mint", "")
+                                   , ("util/mint.toml", "[package]\nsrc = \"lib\"")
+                                   , ("util/lib/strings.mint", "")
+                                   ]);
+        let resolver = Resolver::discover(&dir.join("app/src")).unwrap();
+        let from = dir.join("app/src/nested/other.mint");
+
+        let root = resolver.resolve("shared/list.mint", Some(&from)).unwrap();
+        let dependency = resolver.resolve("util/strings.mint", Some(&from)).unwrap();
+
+        assert_eq!( root, dir.join("app/src/shared/list.mint") );
+        assert_eq!( dependency, dir.join("app/../util/lib/strings.mint") );
+    }
+
+    #[test]
+    fn should_resolve_dependencies_per_package() {
+        let dir = dir("per_package", &[ ("app/mint.toml", "[dependencies]\nutil = { path = \"../util\" }")
+                                      , ("app/main.mint", "")
+                                      , ("util/mint.toml", "[package]\nsrc = \"lib\"\n[dependencies]\nhelper = { path = \"../helper\" }")
+                                      , ("util/lib/strings.mint", "")
+                                      , ("helper/chars.mint", "")
+                                      ]);
+        let resolver = Resolver::discover(&dir.join("app")).unwrap();
+        let from_app = dir.join("app/main.mint");
+        let from_util = dir.join("util/lib/strings.mint");
+
+        let leaked = resolver.resolve("helper/chars.mint", Some(&from_app));
+        let transitive = resolver.resolve("helper/chars.mint", Some(&from_util)).unwrap();
+        let own_root = resolver.resolve("strings.mint", Some(&dir.join("util/lib/nested/other.mint"))).unwrap();
+
+        assert!( matches!( leaked, Err(ResolveError::ModuleNotFound { .. }) ) );
+        assert_eq!( transitive, dir.join("app/../util/../helper/chars.mint") );
+        assert_eq!( own_root, dir.join("app/../util/lib/strings.mint") );
+    }
+
+    #[test]
+    fn should_read_cyclic_dependencies_once() {
+        let dir = dir("cyclic", &[ ("a/mint.toml", "[dependencies]\nb = { path = \"../b\" }")
+                                 , ("b/mint.toml", "[dependencies]\na = { path = \"../a\" }")
+                                 , ("b/x.mint", "")
+                                 ]);
+        let resolver = Resolver::discover(&dir.join("a")).unwrap();
+
+        let output = resolver.resolve("b/x.mint", Some(&dir.join("a/main.mint"))).unwrap();
+
+        assert_eq!( resolver.packages.len(), 2 );
+        assert_eq!( output, dir.join("a/../b/x.mint") );
+    }
+
+    #[test]
+    fn should_fall_back_to_search_path() {
+        let dir = dir("search_path", &[ ("first/a.mint", "")
+                                      , ("second/a.mint", "")
+                                      , ("second/b.mint", "")
+                                      ]);
+        let mut resolver = Resolver::new();
+        resolver.add_search_path(dir.join("first"));
+        resolver.add_search_path(dir.join("second"));
+
+        let a = resolver.resolve("a.mint", None).unwrap();
+        let b = resolver.resolve("b.mint", None).unwrap();
+
+        assert_eq!( a, dir.join("first/a.mint") );
+        assert_eq!( b, dir.join("second/b.mint") );
+    }
+
+    #[test]
+    fn should_list_searched_paths_when_not_found() {
+        let mut resolver = Resolver::new();
+        resolver.add_search_path("/nowhere");
+
+        let output = resolver.resolve("missing.mint", None);
+
+        assert!( matches!( output, Err(ResolveError::ModuleNotFound { searched, .. }) if searched.len() == 2 ) );
+    }
+}
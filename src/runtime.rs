@@ -1,30 +1,91 @@
 
 use std::collections::HashMap;
+use std::cmp::Ordering;
+
+use crate::bignum::{BigInt, Rational};
 
 use purple::data::Func;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct HeapAddress(u64);
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct SymbolId(usize);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum RuntimeData {
     Address(HeapAddress),
     Function(Func),
     Number(f64),
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational),
     String(String),
-    Symbol(String),
+    Char(char),
+    Symbol(SymbolId),
     List(Vec<RuntimeData>),
     Tuple(Vec<RuntimeData>),
+    // NOTE:  Entries are kept sorted by key (see deep_cmp) and keys are unique.
+    Map(Vec<(RuntimeData, RuntimeData)>),
+}
+
+// NOTE:  Symbols are interned so that comparing them compares ids and creating true or false does not allocate.
+pub struct SymbolTable {
+    names : Vec<String>,
+    ids : HashMap<String, SymbolId>,
+}
+
+impl SymbolTable {
+    pub const TRUE : SymbolId = SymbolId(0);
+    pub const FALSE : SymbolId = SymbolId(1);
+
+    pub fn new() -> Self {
+        let mut ret = SymbolTable { names: vec![], ids: HashMap::new() };
+        ret.intern("true");
+        ret.intern("false");
+        ret
+    }
+
+    pub fn intern(&mut self, name : &str) -> SymbolId {
+        match self.ids.get(name) {
+            Some(id) => *id,
+            None => {
+                let id = SymbolId(self.names.len());
+                self.names.push(name.into());
+                self.ids.insert(name.into(), id);
+                id
+            },
+        }
+    }
+
+    pub fn get(&self, name : &str) -> Option<SymbolId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id : SymbolId) -> &str {
+        &self.names[id.0]
+    }
 }
 
+// NOTE:  The symbol table lives here rather than directly on Context because instructions and natives are only given
+// the heap, and string_to_symbol interns new symbols while the program runs.  Context owns it through its heap.
 pub struct Heap {
     heap : HashMap<HeapAddress, RuntimeData>,
     new_address : u64,
+    symbols : SymbolTable,
 }
 
 impl Heap {
     pub fn new() -> Self {
-        Heap { heap : HashMap::new(), new_address : 0 }
+        Heap { heap : HashMap::new(), new_address : 0, symbols : SymbolTable::new() }
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    pub fn symbols_mut(&mut self) -> &mut SymbolTable {
+        &mut self.symbols
     }
 
     pub fn get(&self, address : HeapAddress) -> Option<&RuntimeData> {
@@ -45,4 +106,322 @@ impl Heap {
     pub fn insert(&mut self, address : HeapAddress, data : RuntimeData) {
         self.heap.insert(address, data);
     }
-}
\ No newline at end of file
+}
+
+fn rank(data : &RuntimeData) -> u8 {
+    use RuntimeData::*;
+    match data {
+        Number(_) | Int(_) | BigInt(_) | Rational(_) => 0,
+        Char(_) => 1,
+        String(_) => 2,
+        Symbol(_) => 3,
+        Tuple(_) => 4,
+        List(_) => 5,
+        Map(_) => 6,
+        Function(_) => 7,
+        Address(_) => unreachable!("addresses are followed before they are ranked"),
+    }
+}
+
+// NOTE:  Compares without converting the integer to a float (which loses precision above 2^53).  NaN is greater
+// than every integer.
+pub fn cmp_int_float(i : i64, f : f64) -> Ordering {
+    // NOTE:  2^63 is exactly representable and every float outside of [-2^63, 2^63) is outside of the range of i64.
+    const LIMIT : f64 = 9223372036854775808.0;
+    if f.is_nan() || f >= LIMIT {
+        return Ordering::Less;
+    }
+    if f < -LIMIT {
+        return Ordering::Greater;
+    }
+    let whole = f.trunc();
+    match i.cmp(&(whole as i64)) {
+        Ordering::Equal => 0.0.partial_cmp(&(f - whole)).expect("fractional part of non NaN number is ordered"),
+        o => o,
+    }
+}
+
+fn exact_rank(data : &RuntimeData) -> Option<u8> {
+    use RuntimeData::*;
+    match data {
+        Int(_) => Some(0),
+        BigInt(_) => Some(1),
+        Rational(_) => Some(2),
+        Number(_) => Some(3),
+        _ => None,
+    }
+}
+
+// NOTE:  Compares numbers exactly by converting them all to rationals.  Infinities and NaN are handled separately
+// because they have no rational value.
+fn cmp_exact(a : &RuntimeData, b : &RuntimeData) -> Ordering {
+    fn to_rational(data : &RuntimeData) -> Result<Rational, f64> {
+        match data {
+            RuntimeData::Int(x) => Ok(Rational::from_bigint(BigInt::from_i64(*x))),
+            RuntimeData::BigInt(x) => Ok(Rational::from_bigint(x.clone())),
+            RuntimeData::Rational(x) => Ok(x.clone()),
+            RuntimeData::Number(x) => Rational::from_f64(*x).ok_or(*x),
+            _ => unreachable!("only numbers are compared exactly"),
+        }
+    }
+    fn above(x : f64) -> Ordering {
+        if x.is_nan() || x > 0.0 { Ordering::Greater } else { Ordering::Less }
+    }
+    match (to_rational(a), to_rational(b)) {
+        (Ok(x), Ok(y)) => x.cmp(&y),
+        (Err(x), Ok(_)) => above(x),
+        (Ok(_), Err(y)) => above(y).reverse(),
+        (Err(_), Err(_)) => unreachable!("two Numbers are compared by deep_cmp directly"),
+    }
+}
+
+// NOTE:  Compares numbers by value, exactly across Ints, BigInts, Rationals, and Numbers.  Returns None when either
+// side is NaN or isn't a number.
+pub fn cmp_num(a : &RuntimeData, b : &RuntimeData) -> Option<Ordering> {
+    use RuntimeData::*;
+    match (a, b) {
+        (Number(x), Number(y)) => x.partial_cmp(y),
+        (Number(x), _) | (_, Number(x)) if x.is_nan() => None,
+        (Int(x), Int(y)) => Some(x.cmp(y)),
+        (a, b) if exact_rank(a).is_some() && exact_rank(b).is_some() => Some(cmp_exact(a, b)),
+        _ => None,
+    }
+}
+
+// NOTE:  A total ordering over runtime data that follows heap addresses on either side.  Different kinds of data
+// are ordered numbers < chars < strings < symbols < tuples < lists < maps < functions.  Tuples, lists, and maps (by entry)
+// are ordered lexicographically.  Numbers use their usual ordering (so -0.0 and 0.0 are equal), except NaN is equal to NaN and
+// greater than every other number.  Ints and Numbers are ordered by value, but an Int is less than a Number with the
+// same value so that 1 and 1.0 are not equal.  The same goes for BigInts and Rationals, where equal values are ordered
+// Int < BigInt < Rational < Number.  Returns None when an address cannot be found in the heap.
+pub fn deep_cmp(heap : &Heap, a : &RuntimeData, b : &RuntimeData) -> Option<Ordering> {
+    use RuntimeData::*;
+    match (a, b) {
+        (Address(x), Address(y)) if x == y => Some(Ordering::Equal),
+        (Address(x), b) => deep_cmp(heap, heap.get(*x)?, b),
+        (a, Address(y)) => deep_cmp(heap, a, heap.get(*y)?),
+        (Number(x), Number(y)) => Some(match (x.is_nan(), y.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => x.partial_cmp(y).expect("non NaN numbers are ordered"),
+        }),
+        (Int(x), Int(y)) => Some(x.cmp(y)),
+        (Int(x), Number(y)) => Some(match cmp_int_float(*x, *y) {
+            Ordering::Equal => Ordering::Less,
+            o => o,
+        }),
+        (Number(x), Int(y)) => Some(match cmp_int_float(*y, *x) {
+            Ordering::Equal => Ordering::Greater,
+            o => o.reverse(),
+        }),
+        (a, b) if exact_rank(a).is_some() && exact_rank(b).is_some() => Some(cmp_exact(a, b).then(exact_rank(a).cmp(&exact_rank(b)))),
+        (String(x), String(y)) => Some(x.cmp(y)),
+        (Char(x), Char(y)) => Some(x.cmp(y)),
+        (Symbol(x), Symbol(y)) if x == y => Some(Ordering::Equal),
+        (Symbol(x), Symbol(y)) => Some(heap.symbols().name(*x).cmp(heap.symbols().name(*y))),
+        (Function(x), Function(y)) => Some(x.0.cmp(&y.0)),
+        (List(xs), List(ys)) | (Tuple(xs), Tuple(ys)) => {
+            for (x, y) in std::iter::zip(xs, ys) {
+                match deep_cmp(heap, x, y)? {
+                    Ordering::Equal => { },
+                    o => { return Some(o); },
+                }
+            }
+            Some(xs.len().cmp(&ys.len()))
+        },
+        (Map(xs), Map(ys)) => {
+            for ((xk, xv), (yk, yv)) in std::iter::zip(xs, ys) {
+                match deep_cmp(heap, xk, yk)? {
+                    Ordering::Equal => { },
+                    o => { return Some(o); },
+                }
+                match deep_cmp(heap, xv, yv)? {
+                    Ordering::Equal => { },
+                    o => { return Some(o); },
+                }
+            }
+            Some(xs.len().cmp(&ys.len()))
+        },
+        (a, b) => Some(rank(a).cmp(&rank(b))),
+    }
+}
+
+// NOTE:  Binary search of map entries by key.  Ok is the index of the key and Err is where the key would be
+// inserted.  Returns None when an address cannot be found in the heap.
+pub fn map_search(heap : &Heap, entries : &Vec<(RuntimeData, RuntimeData)>, key : &RuntimeData) -> Option<Result<usize, usize>> {
+    let mut low = 0;
+    let mut high = entries.len();
+    while low < high {
+        let mid = (low + high) / 2;
+        match deep_cmp(heap, &entries[mid].0, key)? {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => { return Some(Ok(mid)); },
+        }
+    }
+    Some(Err(low))
+}
+
+// NOTE:  Inserts or replaces the value for key.  Returns None when an address cannot be found in the heap.
+pub fn map_insert(heap : &Heap, entries : &mut Vec<(RuntimeData, RuntimeData)>, key : RuntimeData, value : RuntimeData) -> Option<()> {
+    match map_search(heap, entries, &key)? {
+        Ok(i) => entries[i] = (key, value),
+        Err(i) => entries.insert(i, (key, value)),
+    }
+    Some(())
+}
+
+// NOTE:  Structural equality that agrees with deep_cmp.  Addresses that cannot be found in the heap are never equal
+// to anything.
+pub fn deep_eq(heap : &Heap, a : &RuntimeData, b : &RuntimeData) -> bool {
+    deep_cmp(heap, a, b) == Some(Ordering::Equal)
+}
+
+// NOTE:  Equality for pins, which follows IEEE float comparison like Number patterns do, so a NaN anywhere in the
+// value is never equal to anything.  Otherwise it agrees with deep_eq.
+pub fn pin_eq(heap : &Heap, a : &RuntimeData, b : &RuntimeData) -> bool {
+    fn has_nan(heap : &Heap, data : &RuntimeData) -> bool {
+        match data {
+            RuntimeData::Address(x) => heap.get(*x).map_or(false, |d| has_nan(heap, d)),
+            RuntimeData::Number(x) => x.is_nan(),
+            RuntimeData::List(xs) | RuntimeData::Tuple(xs) => xs.iter().any(|x| has_nan(heap, x)),
+            RuntimeData::Map(kvps) => kvps.iter().any(|(k, v)| has_nan(heap, k) || has_nan(heap, v)),
+            _ => false,
+        }
+    }
+    deep_eq(heap, a, b) && !has_nan(heap, a)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deep_eq_should_follow_addresses() {
+        let mut heap = Heap::new();
+        let a = heap.insert_new(RuntimeData::List(vec![RuntimeData::Number(1.0)]));
+        let inner = heap.insert_new(RuntimeData::Number(1.0));
+        let b = heap.insert_new(RuntimeData::List(vec![RuntimeData::Address(inner)]));
+
+        assert!( deep_eq(&heap, &RuntimeData::Address(a), &RuntimeData::Address(b)) );
+    }
+
+    #[test]
+    fn deep_eq_should_treat_nan_as_equal_to_itself() {
+        let heap = Heap::new();
+
+        assert!( deep_eq(&heap, &RuntimeData::Number(f64::NAN), &RuntimeData::Number(f64::NAN)) );
+        assert!( deep_eq(&heap, &RuntimeData::Number(0.0), &RuntimeData::Number(-0.0)) );
+    }
+
+    #[test]
+    fn pin_eq_should_never_match_nan() {
+        let heap = Heap::new();
+        let nan = RuntimeData::List(vec![RuntimeData::Number(f64::NAN)]);
+
+        assert!( !pin_eq(&heap, &nan, &nan) );
+        assert!( pin_eq(&heap, &RuntimeData::Number(0.0), &RuntimeData::Number(-0.0)) );
+    }
+
+    #[test]
+    fn deep_cmp_should_order_kinds() {
+        let mut heap = Heap::new();
+        let a = heap.symbols_mut().intern("a");
+        let ordered = vec![ RuntimeData::Number(f64::NAN)
+                          , RuntimeData::Char('b')
+                          , RuntimeData::String("a".into())
+                          , RuntimeData::Symbol(a)
+                          , RuntimeData::Tuple(vec![])
+                          , RuntimeData::List(vec![])
+                          ];
+
+        for (a, b) in std::iter::zip(ordered.iter(), ordered.iter().skip(1)) {
+            assert_eq!( deep_cmp(&heap, a, b), Some(Ordering::Less) );
+        }
+    }
+
+    #[test]
+    fn deep_cmp_should_order_lists_lexicographically() {
+        let heap = Heap::new();
+        let short = RuntimeData::List(vec![RuntimeData::Number(1.0)]);
+        let long = RuntimeData::List(vec![RuntimeData::Number(1.0), RuntimeData::Number(0.0)]);
+        let big = RuntimeData::List(vec![RuntimeData::Number(2.0)]);
+
+        assert_eq!( deep_cmp(&heap, &short, &long), Some(Ordering::Less) );
+        assert_eq!( deep_cmp(&heap, &long, &big), Some(Ordering::Less) );
+    }
+
+    #[test]
+    fn deep_cmp_should_order_symbols_by_name() {
+        let mut heap = Heap::new();
+        let b = heap.symbols_mut().intern("b");
+        let a = heap.symbols_mut().intern("a");
+
+        assert_eq!( deep_cmp(&heap, &RuntimeData::Symbol(a), &RuntimeData::Symbol(b)), Some(Ordering::Less) );
+    }
+
+    #[test]
+    fn symbol_table_should_reuse_ids() {
+        let mut symbols = SymbolTable::new();
+        let a = symbols.intern("a");
+
+        assert_eq!( symbols.intern("a"), a );
+        assert_eq!( symbols.intern("true"), SymbolTable::TRUE );
+        assert_eq!( symbols.name(a), "a" );
+    }
+
+    #[test]
+    fn map_insert_should_keep_keys_sorted_and_unique() {
+        let heap = Heap::new();
+        let mut entries = vec![];
+
+        map_insert(&heap, &mut entries, RuntimeData::String("b".into()), RuntimeData::Number(1.0)).unwrap();
+        map_insert(&heap, &mut entries, RuntimeData::Number(5.0), RuntimeData::Number(2.0)).unwrap();
+        map_insert(&heap, &mut entries, RuntimeData::String("b".into()), RuntimeData::Number(3.0)).unwrap();
+
+        assert_eq!( entries, vec![ (RuntimeData::Number(5.0), RuntimeData::Number(2.0))
+                                 , (RuntimeData::String("b".into()), RuntimeData::Number(3.0))
+                                 ] );
+    }
+
+    #[test]
+    fn deep_cmp_should_order_ints_and_numbers_by_value() {
+        let heap = Heap::new();
+        let ordered = vec![ RuntimeData::Number(f64::NEG_INFINITY)
+                          , RuntimeData::Int(-3)
+                          , RuntimeData::Number(-2.5)
+                          , RuntimeData::Int(1)
+                          , RuntimeData::Number(1.0)
+                          , RuntimeData::Int(9007199254740993)
+                          , RuntimeData::Number(f64::INFINITY)
+                          , RuntimeData::Number(f64::NAN)
+                          ];
+
+        for (a, b) in std::iter::zip(ordered.iter(), ordered.iter().skip(1)) {
+            assert_eq!( deep_cmp(&heap, a, b), Some(Ordering::Less) );
+            assert_eq!( deep_cmp(&heap, b, a), Some(Ordering::Greater) );
+        }
+    }
+
+    #[test]
+    fn deep_cmp_should_order_exact_numbers_by_value() {
+        let heap = Heap::new();
+        let r = |s : &str| RuntimeData::Rational(Rational::parse(s).unwrap());
+        let ordered = vec![ RuntimeData::Number(f64::NEG_INFINITY)
+                          , RuntimeData::BigInt(BigInt::parse("-99999999999999999999").unwrap())
+                          , r("1/3")
+                          , RuntimeData::Number(0.5)
+                          , RuntimeData::Int(1)
+                          , RuntimeData::BigInt(BigInt::from_i64(1))
+                          , r("1")
+                          , RuntimeData::Number(1.0)
+                          , RuntimeData::Number(f64::NAN)
+                          ];
+
+        for (a, b) in std::iter::zip(ordered.iter(), ordered.iter().skip(1)) {
+            assert_eq!( deep_cmp(&heap, a, b), Some(Ordering::Less) );
+            assert_eq!( deep_cmp(&heap, b, a), Some(Ordering::Greater) );
+        }
+    }
+}
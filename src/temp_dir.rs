@@ -0,0 +1,31 @@
+
+use std::path::{Path, PathBuf};
+
+// NOTE:  A directory of files for tests that read from disk.  It is removed when it goes out of scope, and the name
+// along with the process id keeps tests that run at the same time apart.
+pub struct TempDir {
+    path : PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name : &str, files : &[(&str, &str)]) -> Self {
+        let path = std::env::temp_dir().join(format!("mint_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        for (file, content) in files {
+            let file = path.join(file);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        }
+        TempDir { path }
+    }
+
+    pub fn join<P : AsRef<Path>>(&self, path : P) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,140 @@
+
+use std::collections::HashMap;
+
+use crate::ast::TypeAnn;
+use crate::runtime::*;
+use crate::natives::{deref, mismatch};
+use crate::compiling::error::DynamicError;
+
+use super::error::TypeError;
+use super::types::Type;
+
+// NOTE:  Type variables with the same name in one annotation (or in all the annotations of one lambda) are the same
+// variable, so vars is shared between calls for the same scope.  new_var makes the type for each new variable name.
+pub fn to_type(ann : &TypeAnn, position : usize, vars : &mut HashMap<String, Type>, new_var : &mut dyn FnMut(&str) -> Type) -> Result<Type, TypeError> {
+    fn all(anns : &[TypeAnn], position : usize, vars : &mut HashMap<String, Type>, new_var : &mut dyn FnMut(&str) -> Type) -> Result<Vec<Type>, TypeError> {
+        anns.iter().map(|a| to_type(a, position, vars, new_var)).collect()
+    }
+
+    match ann {
+        TypeAnn::Var(name) => Ok(vars.entry(name.clone()).or_insert_with(|| new_var(name)).clone()),
+        TypeAnn::Tuple(anns) => Ok(Type::Tuple(all(anns, position, vars, new_var)?)),
+        TypeAnn::Fun(params, ret) => Ok(Type::Fun(all(params, position, vars, new_var)?, Box::new(to_type(ret, position, vars, new_var)?))),
+        TypeAnn::Named(name, params) => {
+            let mut params = all(params, position, vars, new_var)?;
+            let expected = match name.as_str() {
+                "List" => 1,
+                "Map" => 2,
+                "Number" | "Int" | "BigInt" | "Rational" | "String" | "Char" | "Symbol" => 0,
+                _ => { return Err(TypeError::UnknownType { position, name: name.clone() }); },
+            };
+            if params.len() != expected {
+                return Err(TypeError::WrongTypeArity { position
+                                                     , name: name.clone()
+                                                     , expected
+                                                     , observed: params.len()
+                                                     });
+            }
+            Ok(match name.as_str() {
+                "List" => Type::List(Box::new(params.remove(0))),
+                "Map" => {
+                    let key = params.remove(0);
+                    Type::Map(Box::new(key), Box::new(params.remove(0)))
+                },
+                "Number" => Type::Number,
+                "Int" => Type::Int,
+                "BigInt" => Type::BigInt,
+                "Rational" => Type::Rational,
+                "String" => Type::String,
+                "Char" => Type::Char,
+                _ => Type::Symbol,
+            })
+        },
+    }
+}
+
+// NOTE:  The runtime check for annotations.  Type variables match anything, an Int is a Number, and functions only
+// check that the data can be called (a lambda or a symbol naming a native).  On failure the innermost mismatch is
+// reported.
+pub fn conforms(t : &Type, data : &RuntimeData, heap : &Heap) -> Result<(), DynamicError> {
+    let data = deref(heap, data)?;
+    let fail = || Err(mismatch(&t.to_string(), data));
+    match (t, data) {
+        (Type::Var(_), _) => Ok(()),
+        (Type::Number, RuntimeData::Number(_) | RuntimeData::Int(_)) => Ok(()),
+        (Type::Int, RuntimeData::Int(_)) => Ok(()),
+        (Type::BigInt, RuntimeData::BigInt(_)) => Ok(()),
+        (Type::Rational, RuntimeData::Rational(_)) => Ok(()),
+        (Type::String, RuntimeData::String(_)) => Ok(()),
+        (Type::Char, RuntimeData::Char(_)) => Ok(()),
+        (Type::Symbol, RuntimeData::Symbol(_)) => Ok(()),
+        (Type::List(t), RuntimeData::List(xs)) => xs.iter().try_for_each(|x| conforms(t, x, heap)),
+        (Type::Tuple(ts), RuntimeData::Tuple(xs)) if ts.len() == xs.len() =>
+            ts.iter().zip(xs.iter()).try_for_each(|(t, x)| conforms(t, x, heap)),
+        (Type::Map(k, v), RuntimeData::Map(kvps)) => kvps.iter().try_for_each(|(key, value)| {
+            conforms(k, key, heap)?;
+            conforms(v, value, heap)
+        }),
+        (Type::Fun(_, _), RuntimeData::Function(_) | RuntimeData::Symbol(_)) => Ok(()),
+        _ => fail(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ann(input : &str) -> TypeAnn {
+        let tokens = crate::parsing::tokenizer::tokenize(&format!("let X : {} = 0;", input)).unwrap();
+        let mut top = crate::parsing::parser::parse(&tokens).unwrap();
+        top.lets.remove(0).ann.unwrap()
+    }
+
+    fn convert(input : &str) -> Result<Type, TypeError> {
+        let mut next = 0;
+        to_type(&ann(input), 0, &mut HashMap::new(), &mut |_| { next += 1; Type::Var(next - 1) })
+    }
+
+    #[test]
+    fn should_share_type_variables() {
+        let output = convert("fun(a, List<b>) : {a, b}").unwrap();
+
+        assert_eq!( output.to_string(), "fun(a, List<b>) : {a, b}" );
+    }
+
+    #[test]
+    fn should_reject_unknown_type() {
+        let output = convert("List<Thing>");
+
+        assert!( matches!( output, Err(TypeError::UnknownType { name, .. }) if name == "Thing" ) );
+    }
+
+    #[test]
+    fn should_reject_wrong_arity() {
+        let output = convert("Map<Int>");
+
+        assert!( matches!( output, Err(TypeError::WrongTypeArity { expected: 2, observed: 1, .. }) ) );
+    }
+
+    #[test]
+    fn should_accept_int_as_number() {
+        let heap = Heap::new();
+        let t = convert("List<Number>").unwrap();
+        let data = RuntimeData::List(vec![RuntimeData::Int(1), RuntimeData::Number(2.5)]);
+
+        let output = conforms(&t, &data, &heap);
+
+        assert!( output.is_ok() );
+    }
+
+    #[test]
+    fn should_report_innermost_mismatch() {
+        let heap = Heap::new();
+        let t = convert("List<{Int, String}>").unwrap();
+        let data = RuntimeData::List(vec![RuntimeData::Tuple(vec![RuntimeData::Int(1), RuntimeData::Int(2)])]);
+
+        let output = conforms(&t, &data, &heap);
+
+        assert!( matches!( output, Err(DynamicError::TypeMismatch { expected, observed }) if expected == "String" && observed == "Int" ) );
+    }
+}
@@ -0,0 +1,47 @@
+
+use super::types::{Type, display_together};
+
+// NOTE:  position is where in the source the error was found (see SourceMap::locate).  Display leaves it out because
+// only the caller knows which source it is in.
+#[derive(Debug)]
+pub enum TypeError {
+    Mismatch { position : usize, expected : Type, observed : Type },
+    InfiniteType { position : usize, var : Type, ty : Type },
+    UnknownType { position : usize, name : String },
+    WrongTypeArity { position : usize, name : String, expected : usize, observed : usize },
+    NotCaptured { position : usize, name : String },
+}
+
+impl TypeError {
+    pub fn position(&self) -> usize {
+        match self {
+            TypeError::Mismatch { position, .. } => *position,
+            TypeError::InfiniteType { position, .. } => *position,
+            TypeError::UnknownType { position, .. } => *position,
+            TypeError::WrongTypeArity { position, .. } => *position,
+            TypeError::NotCaptured { position, .. } => *position,
+        }
+    }
+}
+
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TypeError::Mismatch { expected, observed, .. } => {
+                let names = display_together(&[expected, observed]);
+                write!(f, "type mismatch expected {}, but found {}", names[0], names[1])
+            },
+            TypeError::InfiniteType { var, ty, .. } => {
+                let names = display_together(&[var, ty]);
+                write!(f, "infinite type {} occurs in {}", names[0], names[1])
+            },
+            TypeError::UnknownType { name, .. } => write!(f, "unknown type {}", name),
+            TypeError::WrongTypeArity { name, expected, observed, .. } => 
+                write!(f, "type {} expected {} parameters, but found {}", name, expected, observed),
+            TypeError::NotCaptured { name, .. } =>
+                write!(f, "variable {} is local to an enclosing scope, but lambdas only see globals and their own variables", name),
+        }
+    }
+}
+
+impl std::error::Error for TypeError {}
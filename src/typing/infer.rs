@@ -0,0 +1,803 @@
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use crate::natives::Natives;
+
+use super::annotation;
+use super::error::TypeError;
+use super::types::{Type, Scheme};
+
+pub type Env = HashMap<String, Scheme>;
+
+// NOTE:  Infers the types of a top level, adding the generalized type of every let binding to env.  Returns the type
+// of the final expression when there is one.  env is left alone when any of it fails to check.
+pub fn check(top : &Top, env : &mut Env, natives : &Natives) -> Result<Option<Type>, TypeError> {
+    let mut infer = Infer::new(env, natives);
+    let mut checked = env.clone();
+    let ret = infer.top(top, &mut checked, 0)?;
+    for scheme in checked.values_mut() {
+        scheme.ty = infer.resolve(&scheme.ty);
+    }
+    *env = checked;
+    Ok(ret.map(|t| infer.resolve(&t)))
+}
+
+pub fn infer_expr(expr : &Expr, env : &Env, natives : &Natives) -> Result<Type, TypeError> {
+    let mut infer = Infer::new(env, natives);
+    let t = infer.expr(expr, env, 0)?;
+    Ok(infer.resolve(&t))
+}
+
+enum Failure {
+    Mismatch,
+    Infinite(Type, Type),
+}
+
+// NOTE:  returns is the result type of the lambda whose body is being checked, which a ? can return from early.
+// Lambdas don't capture (see compiler::compile_literal), so a lambda body only sees globals and its own variables.
+// globals is None at the top level, where every variable is a global, and hidden holds the locals of the enclosing
+// scopes that the current lambda body can't see.
+struct Infer<'a> {
+    bindings : HashMap<usize, Type>,
+    next : usize,
+    natives : &'a Natives,
+    returns : Option<Type>,
+    globals : Option<Env>,
+    hidden : HashSet<String>,
+}
+
+impl<'a> Infer<'a> {
+    // NOTE:  New variables start after every variable in env so that types left over from earlier checks (like in the
+    // repl) don't collide.
+    fn new(env : &Env, natives : &'a Natives) -> Self {
+        let next = env.values()
+                      .flat_map(|s| s.ty.free_vars().into_iter().chain(s.vars.iter().copied()))
+                      .max()
+                      .map_or(0, |x| x + 1);
+        Infer { bindings: HashMap::new(), next, natives, returns: None, globals: None, hidden: HashSet::new() }
+    }
+
+    fn fresh(&mut self) -> Type {
+        self.next += 1;
+        Type::Var(self.next - 1)
+    }
+
+    fn shallow(&self, t : &Type) -> Type {
+        let mut t = t.clone();
+        while let Type::Var(x) = t {
+            match self.bindings.get(&x) {
+                Some(bound) => t = bound.clone(),
+                None => break,
+            }
+        }
+        t
+    }
+
+    fn resolve(&self, t : &Type) -> Type {
+        match self.shallow(t) {
+            Type::List(t) => Type::List(Box::new(self.resolve(&t))),
+            Type::Tuple(ts) => Type::Tuple(ts.iter().map(|t| self.resolve(t)).collect()),
+            Type::Map(k, v) => Type::Map(Box::new(self.resolve(&k)), Box::new(self.resolve(&v))),
+            Type::Fun(ps, r) => Type::Fun(ps.iter().map(|t| self.resolve(t)).collect(), Box::new(self.resolve(&r))),
+            t => t,
+        }
+    }
+
+    fn unify(&mut self, expected : &Type, observed : &Type, position : usize) -> Result<(), TypeError> {
+        match self.unify_inner(expected, observed) {
+            Ok(()) => Ok(()),
+            Err(Failure::Mismatch) => Err(TypeError::Mismatch { position
+                                                              , expected: self.resolve(expected)
+                                                              , observed: self.resolve(observed)
+                                                              }),
+            Err(Failure::Infinite(var, ty)) => Err(TypeError::InfiniteType { position
+                                                                           , var
+                                                                           , ty: self.resolve(&ty)
+                                                                           }),
+        }
+    }
+
+    fn unify_inner(&mut self, a : &Type, b : &Type) -> Result<(), Failure> {
+        fn all(infer : &mut Infer, xs : &[Type], ys : &[Type]) -> Result<(), Failure> {
+            for (x, y) in xs.iter().zip(ys.iter()) {
+                infer.unify_inner(x, y)?;
+            }
+            Ok(())
+        }
+
+        match (self.shallow(a), self.shallow(b)) {
+            (Type::Var(x), Type::Var(y)) if x == y => Ok(()),
+            (Type::Var(x), t) | (t, Type::Var(x)) => {
+                if self.resolve(&t).free_vars().contains(&x) {
+                    return Err(Failure::Infinite(Type::Var(x), t));
+                }
+                self.bindings.insert(x, t);
+                Ok(())
+            },
+            (Type::List(x), Type::List(y)) => self.unify_inner(&x, &y),
+            (Type::Tuple(xs), Type::Tuple(ys)) if xs.len() == ys.len() => all(self, &xs, &ys),
+            (Type::Map(k1, v1), Type::Map(k2, v2)) => {
+                self.unify_inner(&k1, &k2)?;
+                self.unify_inner(&v1, &v2)
+            },
+            (Type::Fun(ps, r), Type::Fun(qs, s)) if ps.len() == qs.len() => {
+                all(self, &ps, &qs)?;
+                self.unify_inner(&r, &s)
+            },
+            // NOTE:  Each use of a native as a function gets a fresh copy of its signature.  Natives without a
+            // signature can be any function.
+            (Type::Native(_), Type::Symbol) | (Type::Symbol, Type::Native(_)) => Ok(()),
+            (Type::Native(name), f @ Type::Fun(_, _)) | (f @ Type::Fun(_, _), Type::Native(name)) => match self.native(&name) {
+                Some(t) => self.unify_inner(&t, &f),
+                None => Ok(()),
+            },
+            (x, y) if x == y => Ok(()),
+            _ => Err(Failure::Mismatch),
+        }
+    }
+
+    // NOTE:  Checks the type of a value against an annotation.  This is the only place an Int is accepted as a Number
+    // (unification is symmetric, so doing it there would make the result depend on the order things are checked in).
+    // Lists, tuples, and maps can't change, so their items are checked the same way.  Functions aren't, because an
+    // Int parameter doesn't accept every Number.
+    fn subsume(&mut self, ann : &Type, value : &Type, position : usize) -> Result<(), TypeError> {
+        match (self.shallow(ann), self.shallow(value)) {
+            (Type::Number, Type::Int) => Ok(()),
+            (Type::List(a), Type::List(v)) => self.subsume(&a, &v, position),
+            (Type::Tuple(a), Type::Tuple(v)) if a.len() == v.len() => {
+                std::iter::zip(a.iter(), v.iter()).try_for_each(|(a, v)| self.subsume(a, v, position))
+            },
+            (Type::Map(ak, av), Type::Map(vk, vv)) => {
+                self.subsume(&ak, &vk, position)?;
+                self.subsume(&av, &vv, position)
+            },
+            _ => self.unify(ann, value, position),
+        }
+    }
+
+    fn instantiate(&mut self, scheme : &Scheme) -> Type {
+        let fresh = scheme.vars.iter().map(|v| (*v, self.fresh())).collect::<HashMap<_, _>>();
+        fn sub(t : &Type, fresh : &HashMap<usize, Type>) -> Type {
+            match t {
+                Type::Var(x) => fresh.get(x).cloned().unwrap_or(Type::Var(*x)),
+                Type::List(t) => Type::List(Box::new(sub(t, fresh))),
+                Type::Tuple(ts) => Type::Tuple(ts.iter().map(|t| sub(t, fresh)).collect()),
+                Type::Map(k, v) => Type::Map(Box::new(sub(k, fresh)), Box::new(sub(v, fresh))),
+                Type::Fun(ps, r) => Type::Fun(ps.iter().map(|t| sub(t, fresh)).collect(), Box::new(sub(r, fresh))),
+                t => t.clone(),
+            }
+        }
+        sub(&scheme.ty, &fresh)
+    }
+
+    // NOTE:  Annotation type variables are rigid while the let or lambda they annotate is checked, so that
+    // fun(X : a) : a { 1 } is rejected.  Afterwards they become ordinary variables so that the result can be
+    // generalized.
+    fn loosen(&mut self, t : &Type, vars : &HashMap<String, Type>) -> Type {
+        fn sub(t : &Type, fresh : &HashMap<usize, Type>) -> Type {
+            match t {
+                Type::Rigid(x, _) => fresh.get(x).cloned().unwrap_or_else(|| t.clone()),
+                Type::List(t) => Type::List(Box::new(sub(t, fresh))),
+                Type::Tuple(ts) => Type::Tuple(ts.iter().map(|t| sub(t, fresh)).collect()),
+                Type::Map(k, v) => Type::Map(Box::new(sub(k, fresh)), Box::new(sub(v, fresh))),
+                Type::Fun(ps, r) => Type::Fun(ps.iter().map(|t| sub(t, fresh)).collect(), Box::new(sub(r, fresh))),
+                t => t.clone(),
+            }
+        }
+        let mut fresh = HashMap::new();
+        for v in vars.values() {
+            if let Type::Rigid(x, _) = v {
+                let var = self.fresh();
+                fresh.insert(*x, var);
+            }
+        }
+        sub(&self.resolve(t), &fresh)
+    }
+
+    fn generalize(&self, env : &Env, t : &Type) -> Scheme {
+        let t = self.resolve(t);
+        let in_env = env.values()
+                        .flat_map(|s| Scheme { vars: s.vars.clone(), ty: self.resolve(&s.ty) }.free_vars())
+                        .collect::<Vec<_>>();
+        let vars = t.free_vars().into_iter().filter(|v| !in_env.contains(v)).collect();
+        Scheme { vars, ty: t }
+    }
+
+    // NOTE:  Errors are reported at the position of the closest enclosing expression that has one.  position is used
+    // for literals that aren't inside of a let or a call, like the final expression of a block.
+    fn top(&mut self, top : &Top, env : &mut Env, position : usize) -> Result<Option<Type>, TypeError> {
+        for l in &top.lets {
+            let position = l.position;
+            // NOTE:  The pattern binds the annotated type when there is one, so let X : Number = 5; binds a Number.
+            let mut expr = self.expr(&l.expr, env, position)?;
+            let mut ann_vars = HashMap::new();
+            if let Some(ann) = &l.ann {
+                let ann = self.annotation(ann, position, &mut ann_vars)?;
+                self.subsume(&ann, &expr, position)?;
+                expr = ann;
+            }
+            let mut bound = vec![];
+            let pat = self.pattern(&l.pattern, env, &mut bound, position)?;
+            self.unify(&pat, &expr, position)?;
+
+            let schemes = bound.iter().map(|(name, t)| {
+                let t = self.loosen(t, &ann_vars);
+                (name.clone(), self.generalize(env, &t))
+            }).collect::<Vec<_>>();
+            env.extend(schemes);
+        }
+
+        match &top.expr {
+            Some(expr) => Ok(Some(self.expr(expr, env, position)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn expr(&mut self, expr : &Expr, env : &Env, position : usize) -> Result<Type, TypeError> {
+        match expr {
+            Expr::Literal(lit) => self.literal(lit, env, position),
+            Expr::Call(position, func, params) => {
+                let position = *position;
+                let func = self.expr(func, env, position)?;
+                let params = params.iter().map(|p| self.expr(p, env, position)).collect::<Result<Vec<_>, _>>()?;
+                self.call(func, params, position)
+            },
+            Expr::Method(position, receiver, name, params) => {
+                let position = *position;
+                let func = self.native_or_fresh(name);
+                let params = std::iter::once(&**receiver).chain(params.iter()).map(|p| self.expr(p, env, position)).collect::<Result<Vec<_>, _>>()?;
+                self.call(func, params, position)
+            },
+            Expr::Interpolate(position, parts) => {
+                for part in parts {
+                    self.expr(part, env, *position)?;
+                }
+                Ok(Type::String)
+            },
+            // NOTE:  Raised values aren't typed, so handler patterns can match anything.  Every handler has to result
+            // in the same type as the block.
+            Expr::Try(position, block, handlers) => {
+                let outer_globals = self.globals.clone();
+                self.globals.get_or_insert_with(|| env.clone());
+                let t = self.try_catch(block, handlers, env, *position);
+                self.globals = outer_globals;
+                t
+            },
+            // NOTE:  Both tuples have to have the same type, so the error value has to have the same type as the ok
+            // value.  The {error, E} tuple is returned as is, so the lambda has to result in the same type.
+            Expr::Propagate(position, expr) => {
+                let position = *position;
+                let t = self.expr(expr, env, position)?;
+                let value = self.fresh();
+                self.unify(&Type::Tuple(vec![Type::Symbol, value.clone()]), &t, position)?;
+                if let Some(returns) = self.returns.clone() {
+                    self.unify(&returns, &t, position)?;
+                }
+                Ok(value)
+            },
+        }
+    }
+
+    fn try_catch(&mut self, block : &Top, handlers : &[(Pat, Expr)], env : &Env, position : usize) -> Result<Type, TypeError> {
+        let ret = match self.top(block, &mut env.clone(), position)? {
+            Some(t) => t,
+            None => Type::Tuple(vec![]),
+        };
+        for (pattern, handler) in handlers {
+            let mut bound = vec![];
+            self.pattern(pattern, env, &mut bound, position)?;
+            let mut handler_env = env.clone();
+            handler_env.extend(bound.into_iter().map(|(name, t)| (name, Scheme::mono(t))));
+            let t = self.expr(handler, &handler_env, position)?;
+            self.unify(&ret, &t, position)?;
+        }
+        Ok(ret)
+    }
+
+    // NOTE:  Natives without a signature can be anything.
+    fn native_or_fresh(&mut self, name : &str) -> Type {
+        match self.native(name) {
+            Some(t) => t,
+            None => self.fresh(),
+        }
+    }
+
+    // NOTE:  When the function type is already known, each parameter is unified on its own so that mismatches point at
+    // the parameter instead of the whole function.
+    fn call(&mut self, func : Type, params : Vec<Type>, position : usize) -> Result<Type, TypeError> {
+        let func = match self.shallow(&func) {
+            Type::Native(name) => self.native_or_fresh(&name),
+            func => func,
+        };
+        match self.shallow(&func) {
+            Type::Fun(ps, r) if ps.len() == params.len() => {
+                for (expected, observed) in ps.iter().zip(params.iter()) {
+                    self.unify(expected, observed, position)?;
+                }
+                Ok(*r)
+            },
+            _ => {
+                let ret = self.fresh();
+                self.unify(&func, &Type::Fun(params, Box::new(ret.clone())), position)?;
+                Ok(ret)
+            },
+        }
+    }
+
+    fn literal(&mut self, lit : &Lit, env : &Env, position : usize) -> Result<Type, TypeError> {
+        match lit {
+            Lit::Number(_) => Ok(Type::Number),
+            Lit::Int(_) => Ok(Type::Int),
+            Lit::BigInt(_) => Ok(Type::BigInt),
+            Lit::Rational(_) => Ok(Type::Rational),
+            Lit::String(_) => Ok(Type::String),
+            Lit::Char(_) => Ok(Type::Char),
+            Lit::Symbol(name) if self.natives.contains(name) => Ok(Type::Native(name.clone())),
+            Lit::Symbol(_) => Ok(Type::Symbol),
+            // NOTE:  Variables this checker hasn't seen (like imports) can be anything.  The compiler reports the
+            // ones that really aren't defined.
+            Lit::Variable(name) => match env.get(name) {
+                Some(scheme) => Ok(self.instantiate(scheme)),
+                None if self.hidden.contains(name) => Err(TypeError::NotCaptured { position, name: name.clone() }),
+                None => Ok(self.fresh()),
+            },
+            Lit::List(items) => {
+                let item = self.fresh();
+                for x in items {
+                    let t = self.literal(x, env, position)?;
+                    self.unify(&item, &t, position)?;
+                }
+                Ok(Type::List(Box::new(item)))
+            },
+            Lit::Tuple(items) => Ok(Type::Tuple(items.iter().map(|x| self.literal(x, env, position)).collect::<Result<_, _>>()?)),
+            Lit::Map(kvps) => {
+                let (key, value) = (self.fresh(), self.fresh());
+                for (k, v) in kvps {
+                    let kt = self.literal(k, env, position)?;
+                    self.unify(&key, &kt, position)?;
+                    let vt = self.literal(v, env, position)?;
+                    self.unify(&value, &vt, position)?;
+                }
+                Ok(Type::Map(Box::new(key), Box::new(value)))
+            },
+            Lit::Lambda(lambda) => {
+                let globals = self.globals.clone().unwrap_or_else(|| env.clone());
+                let mut hidden = self.hidden.clone();
+                hidden.extend(env.keys().filter(|name| !globals.contains_key(*name)).cloned());
+
+                let outer_globals = self.globals.replace(globals.clone());
+                let outer_hidden = std::mem::replace(&mut self.hidden, hidden);
+                let t = self.lambda(lambda, globals);
+                self.globals = outer_globals;
+                self.hidden = outer_hidden;
+                t
+            },
+        }
+    }
+
+    // NOTE:  body_env starts out with only the globals.  The parameters can pin earlier parameters.
+    fn lambda(&mut self, lambda : &Lambda, mut body_env : Env) -> Result<Type, TypeError> {
+        let position = lambda.position;
+        let mut ann_vars = HashMap::new();
+        let mut params = vec![];
+        for (p, ann) in lambda.params.iter().zip(lambda.param_anns.iter()) {
+            let mut bound = vec![];
+            let t = self.pattern(p, &body_env, &mut bound, position)?;
+            if let Some(ann) = ann {
+                let ann = self.annotation(ann, position, &mut ann_vars)?;
+                self.unify(&ann, &t, position)?;
+            }
+            params.push(t);
+            body_env.extend(bound.into_iter().map(|(name, t)| (name, Scheme::mono(t))));
+        }
+        for clause in &lambda.requires {
+            let t = self.expr(clause, &body_env, position)?;
+            self.unify(&Type::Symbol, &t, position)?;
+        }
+        let returns = self.fresh();
+        let outer_returns = self.returns.replace(returns.clone());
+        let body = self.top(&lambda.body, &mut body_env.clone(), position);
+        self.returns = outer_returns;
+        let ret = match body? {
+            Some(t) => t,
+            None => Type::Tuple(vec![]),
+        };
+        self.unify(&returns, &ret, position)?;
+        for (var, clause) in &lambda.ensures {
+            let mut ensures_env = body_env.clone();
+            ensures_env.insert(var.clone(), Scheme::mono(ret.clone()));
+            let t = self.expr(clause, &ensures_env, position)?;
+            self.unify(&Type::Symbol, &t, position)?;
+        }
+        // NOTE:  Like a let, the lambda results in the annotated type when there is one.
+        let ret = match &lambda.ret_ann {
+            Some(ann) => {
+                let ann = self.annotation(ann, position, &mut ann_vars)?;
+                self.subsume(&ann, &ret, position)?;
+                ann
+            },
+            None => ret,
+        };
+        Ok(self.loosen(&Type::Fun(params, Box::new(ret)), &ann_vars))
+    }
+
+    fn annotation(&mut self, ann : &TypeAnn, position : usize, vars : &mut HashMap<String, Type>) -> Result<Type, TypeError> {
+        annotation::to_type(ann, position, vars, &mut |name| {
+            self.next += 1;
+            Type::Rigid(self.next - 1, name.into())
+        })
+    }
+
+    fn pattern(&mut self, pat : &Pat, env : &Env, bound : &mut Vec<(String, Type)>, position : usize) -> Result<Type, TypeError> {
+        match pat {
+            Pat::Wild => Ok(self.fresh()),
+            Pat::Number(_) => Ok(Type::Number),
+            Pat::Int(_) => Ok(Type::Int),
+            Pat::BigInt(_) => Ok(Type::BigInt),
+            Pat::Rational(_) => Ok(Type::Rational),
+            Pat::Char(_) => Ok(Type::Char),
+            // NOTE:  Ranges match both Number and Int.
+            Pat::Range(_, _) => Ok(self.fresh()),
+            Pat::String(_) => Ok(Type::String),
+            Pat::Symbol(_) => Ok(Type::Symbol),
+            Pat::List(prefix, rest, suffix) => {
+                let item = self.fresh();
+                for p in prefix.iter().chain(suffix.iter()) {
+                    let t = self.pattern(p, env, bound, position)?;
+                    self.unify(&item, &t, position)?;
+                }
+                let list = Type::List(Box::new(item));
+                if let Some(rest) = rest {
+                    let t = self.pattern(rest, env, bound, position)?;
+                    self.unify(&list, &t, position)?;
+                }
+                Ok(list)
+            },
+            Pat::Tuple(ps) => Ok(Type::Tuple(ps.iter().map(|p| self.pattern(p, env, bound, position)).collect::<Result<_, _>>()?)),
+            Pat::Variable(name) => {
+                let t = self.fresh();
+                bound.push((name.clone(), t.clone()));
+                Ok(t)
+            },
+            Pat::At(name, p) => {
+                let t = self.pattern(p, env, bound, position)?;
+                bound.push((name.clone(), t.clone()));
+                Ok(t)
+            },
+            // NOTE:  Every alternative binds the same variables (see compiler::check_pattern), so the bindings of the
+            // later alternatives are unified with the first.
+            Pat::Or(ps) => {
+                let t = self.fresh();
+                let mut first = None;
+                for p in ps {
+                    let mut alt_bound = vec![];
+                    let alt = self.pattern(p, env, &mut alt_bound, position)?;
+                    self.unify(&t, &alt, position)?;
+                    alt_bound.sort_by(|a, b| a.0.cmp(&b.0));
+                    match &first {
+                        None => first = Some(alt_bound),
+                        Some(first) => {
+                            for ((_, x), (_, y)) in first.iter().zip(alt_bound.iter()) {
+                                self.unify(x, y, position)?;
+                            }
+                        },
+                    }
+                }
+                bound.extend(first.unwrap_or_default());
+                Ok(t)
+            },
+            Pat::StringConcat(a, b) => {
+                let a = self.pattern(a, env, bound, position)?;
+                self.unify(&Type::String, &a, position)?;
+                let b = self.pattern(b, env, bound, position)?;
+                self.unify(&Type::String, &b, position)?;
+                Ok(Type::String)
+            },
+            Pat::Pin(name) => match env.get(name) {
+                Some(scheme) => Ok(self.instantiate(scheme)),
+                None if self.hidden.contains(name) => Err(TypeError::NotCaptured { position, name: name.clone() }),
+                None => Ok(self.fresh()),
+            },
+            Pat::Map(kvps) => {
+                let (key, value) = (self.fresh(), self.fresh());
+                for (k, p) in kvps {
+                    let kt = self.literal(k, env, position)?;
+                    self.unify(&key, &kt, position)?;
+                    let vt = self.pattern(p, env, bound, position)?;
+                    self.unify(&value, &vt, position)?;
+                }
+                Ok(Type::Map(Box::new(key), Box::new(value)))
+            },
+        }
+    }
+
+    // NOTE:  Natives that work on several unrelated types (like add over the numeric tower or get returning either
+    // {some, X} or none) don't have a signature.
+    fn native(&mut self, name : &str) -> Option<Type> {
+        use Type::*;
+
+        fn fun(ps : Vec<Type>, r : Type) -> Type { Fun(ps, Box::new(r)) }
+        fn list(t : &Type) -> Type { List(Box::new(t.clone())) }
+        fn map(k : &Type, v : &Type) -> Type { Map(Box::new(k.clone()), Box::new(v.clone())) }
+
+        let (a, b) = (self.fresh(), self.fresh());
+        let predicate = fun(vec![a.clone()], Symbol);
+
+        Some(match name {
+            "==" => fun(vec![a.clone(), a], Symbol),
+            "compare" => fun(vec![a, b], Symbol),
+            "length" => fun(vec![list(&a)], Int),
+            "is_empty" => fun(vec![list(&a)], Symbol),
+            "map" => fun(vec![list(&a), fun(vec![a], b.clone())], list(&b)),
+            "filter" => fun(vec![list(&a), predicate], list(&a)),
+            "fold_left" => fun(vec![list(&a), b.clone(), fun(vec![b.clone(), a], b.clone())], b),
+            "fold_right" => fun(vec![list(&a), b.clone(), fun(vec![a, b.clone()], b.clone())], b),
+            "reverse" => fun(vec![list(&a)], list(&a)),
+            "append" => fun(vec![list(&a), list(&a)], list(&a)),
+            "zip" => fun(vec![list(&a), list(&b)], List(Box::new(Tuple(vec![a, b])))),
+            "nth" => fun(vec![list(&a), Int], a),
+            "take" | "drop" => fun(vec![list(&a), Int], list(&a)),
+            "sort_by" => fun(vec![list(&a), fun(vec![a.clone(), a.clone()], Symbol)], list(&a)),
+            "any" | "all" => fun(vec![list(&a), predicate], Symbol),
+            "put" => fun(vec![map(&a, &b), a.clone(), b.clone()], map(&a, &b)),
+            "remove" => fun(vec![map(&a, &b), a.clone()], map(&a, &b)),
+            "keys" => fun(vec![map(&a, &b)], list(&a)),
+            "values" => fun(vec![map(&a, &b)], list(&b)),
+            "merge" => fun(vec![map(&a, &b), map(&a, &b)], map(&a, &b)),
+            "to_int" => fun(vec![a], Int),
+            "to_float" => fun(vec![a], Number),
+            "to_bigint" => fun(vec![a], BigInt),
+            "to_rational" => fun(vec![a], Rational),
+            "string_length" => fun(vec![String], Int),
+            "split" => fun(vec![String, String], list(&String)),
+            "join" => fun(vec![list(&String), String], String),
+            "trim" | "to_upper" | "to_lower" => fun(vec![String], String),
+            "contains" | "starts_with" | "ends_with" => fun(vec![String, String], Symbol),
+            "replace" => fun(vec![String, String, String], String),
+            "substring" => fun(vec![String, Int, Int], String),
+            "symbol_to_string" => fun(vec![Symbol], String),
+            "string_to_symbol" => fun(vec![String], Symbol),
+            "to_string" => fun(vec![a], String),
+            "chars" => fun(vec![String], list(&Char)),
+            "from_chars" => fun(vec![list(&Char)], String),
+            "char_to_int" => fun(vec![Char], Int),
+            "int_to_char" => fun(vec![Int], Char),
+            "raise" => fun(vec![a], b),
+            _ => { return None; },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn infer(input : &str) -> Result<Option<Type>, TypeError> {
+        let tokens = crate::parsing::tokenizer::tokenize(input).unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+        check(&top, &mut Env::new(), &Natives::new())
+    }
+
+    #[test]
+    fn should_infer_let_polymorphism() {
+        let output = infer("let Id = fun(X) { X }; let A = Id(1); let B = Id(\"a\"); {A, B}").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "{Int, String}" );
+    }
+
+    #[test]
+    fn should_infer_lambda() {
+        let output = infer("fun(X, Y) { [X, Y] }").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "fun(a, a) : List<a>" );
+    }
+
+    #[test]
+    fn should_infer_natives_as_functions() {
+        let output = infer("map([[1], [2, 3]], length)").unwrap().unwrap();
+
+        assert_eq!( output, Type::List(Box::new(Type::Int)) );
+    }
+
+    #[test]
+    fn should_infer_pattern_bindings() {
+        let output = infer("let {X, [Y | _]} = {ok, [\"a\"]}; {Y, X}").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "{String, Symbol}" );
+    }
+
+    #[test]
+    fn should_type_native_names_as_symbols() {
+        let output = infer("let {map, X} = {map, 1}; {length, X}").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "{Symbol, Int}" );
+    }
+
+    #[test]
+    fn should_call_natives_through_variables() {
+        let output = infer("let F = length; let Xs = [[1], [2, 3]]; {F([1]), map(Xs, F), F}").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "{Int, List<Int>, Symbol}" );
+    }
+
+    #[test]
+    fn should_check_natives_called_through_variables() {
+        let output = infer("let F = length; F(1)");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { .. }) ) );
+    }
+
+    #[test]
+    fn should_check_natives_passed_as_functions() {
+        let output = infer("map([1, 2], length)");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { .. }) ) );
+    }
+
+    #[test]
+    fn should_leave_env_alone_on_failure() {
+        let tokens = crate::parsing::tokenizer::tokenize("let X = 1; let Y = length(X);").unwrap();
+        let top = crate::parsing::parser::parse(&tokens).unwrap();
+        let mut env = Env::new();
+
+        let output = check(&top, &mut env, &Natives::new());
+
+        assert!( output.is_err() );
+        assert!( env.is_empty() );
+    }
+
+    #[test]
+    fn should_report_parameter_mismatch() {
+        let output = infer("let X = length(1);");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { position: 14, expected: Type::List(_), observed: Type::Int }) ) );
+    }
+
+    #[test]
+    fn should_report_mixed_list() {
+        let output = infer("[1, \"a\"]");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { expected: Type::Int, observed: Type::String, .. }) ) );
+    }
+
+    #[test]
+    fn should_report_infinite_type() {
+        let output = infer("fun(X) { X(X) }");
+
+        assert!( matches!( output, Err(TypeError::InfiniteType { .. }) ) );
+    }
+
+    #[test]
+    fn should_check_annotations() {
+        let output = infer("let F = fun(X : Int) : String { X };");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { position: 8, expected: Type::String, observed: Type::Int }) ) );
+    }
+
+    #[test]
+    fn should_use_annotations() {
+        let output = infer("let F = fun(X : Int, Y) { [X, Y] }; let Z : List<Int> = []; F").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "fun(Int, Int) : List<Int>" );
+    }
+
+    #[test]
+    fn should_accept_int_as_number() {
+        let output = infer("let X : Number = 5; let F = fun(Y : Number) : Number { Y }; F(X)").unwrap().unwrap();
+
+        assert_eq!( output, Type::Number );
+    }
+
+    #[test]
+    fn should_accept_int_as_number_only_against_annotations() {
+        let output = infer("let F = fun(X) : List<Number> { [X, 2] }; F(1)").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "List<Number>" );
+    }
+
+    #[test]
+    fn should_not_mix_int_and_number_in_either_order() {
+        for input in ["[1.5, 2]", "[2, 1.5]", "1.5 == 1", "1 == 1.5"] {
+            assert!( matches!( infer(input), Err(TypeError::Mismatch { .. }) ), "{}", input );
+        }
+    }
+
+    #[test]
+    fn should_not_accept_int_function_as_number_function() {
+        let output = infer("let F : fun(Number) : Number = fun(X : Int) : Int { X };");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { .. }) ) );
+    }
+
+    #[test]
+    fn should_not_accept_number_as_int() {
+        let output = infer("let X : Int = 1.5;");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { expected: Type::Int, observed: Type::Number, .. }) ) );
+    }
+
+    #[test]
+    fn should_keep_annotation_variables_rigid() {
+        let output = infer("let F = fun(X : a) : a { 1 };");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { expected: Type::Rigid(_, ref a), observed: Type::Int, .. }) if a == "a" ) );
+    }
+
+    #[test]
+    fn should_generalize_annotation_variables() {
+        let output = infer("let Id = fun(X : a) : a { X }; {Id(1), Id(\"b\")}").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "{Int, String}" );
+    }
+
+    #[test]
+    fn should_report_annotation_mismatch() {
+        let output = infer("let X : String = 1;");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { expected: Type::String, observed: Type::Int, .. }) ) );
+    }
+
+    #[test]
+    fn should_check_contract_clauses() {
+        let output = infer("let F = fun(X) requires is_empty(X) ensures R -> R == \"a\" { length(X) };");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { expected: Type::Int, observed: Type::String, .. }) ) );
+    }
+
+    #[test]
+    fn should_allow_untyped_natives() {
+        let output = infer("fold_left([1, 2], 0, add)").unwrap().unwrap();
+
+        assert_eq!( output, Type::Int );
+    }
+
+    #[test]
+    fn should_unify_handlers_with_try_block() {
+        let output = infer("try { 1 } catch {oops, X} => X catch _ => \"a\"");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { expected: Type::Int, observed: Type::String, .. }) ) );
+    }
+
+    #[test]
+    fn should_unwrap_propagated_tuple() {
+        let output = infer("fun(X) { let Y = {ok, X}?; let Z = [Y, 1]; {ok, Y} }").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "fun(Int) : {Symbol, Int}" );
+    }
+
+    #[test]
+    fn should_unify_propagated_error_with_lambda_result() {
+        let output = infer("fun(X) { let Y = X?; length(Y) }");
+
+        assert!( matches!( output, Err(TypeError::Mismatch { .. }) ) );
+    }
+
+    #[test]
+    fn should_propagate_from_innermost_lambda() {
+        let output = infer("fun(X) { let F = fun(Y) { let Z = Y?; {ok, Z} }; [F(X), X] }").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "fun({Symbol, a}) : List<{Symbol, a}>" );
+    }
+
+    #[test]
+    fn should_report_enclosing_lambda_locals_used_in_lambda() {
+        let output = infer("fun(X, Xs) { map(Xs, fun(Y) { add(X, Y) }) }");
+
+        assert!( matches!( output, Err(TypeError::NotCaptured { name, .. }) if name == "X" ) );
+    }
+
+    #[test]
+    fn should_report_try_locals_used_in_lambda() {
+        let output = infer("try { let X = 1; fun() { X } } catch _ => fun() { 2 }");
+
+        assert!( matches!( output, Err(TypeError::NotCaptured { name, .. }) if name == "X" ) );
+    }
+
+    #[test]
+    fn should_let_lambdas_use_globals_and_their_own_variables() {
+        let output = infer("let X = 1; fun(Y) { let Z = Y; fun(W) { [X, W] } }").unwrap().unwrap();
+
+        assert_eq!( output.to_string(), "fun(a) : fun(Int) : List<Int>" );
+    }
+}
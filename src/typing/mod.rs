@@ -0,0 +1,5 @@
+
+pub mod error;
+pub mod types;
+pub mod infer;
+pub mod annotation;
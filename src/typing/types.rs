@@ -0,0 +1,125 @@
+
+use std::collections::HashMap;
+
+// NOTE:  All symbols share the nominal type Symbol.  There aren't any union types, so natives like get and find that
+// return either {some, X} or none are left untyped (see infer::native).  Int is accepted where a Number is expected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Var(usize),
+    // NOTE:  A type variable written in an annotation along with its name.  It only unifies with itself and with
+    // ordinary variables (see Infer::loosen).
+    Rigid(usize, String),
+    Number,
+    Int,
+    BigInt,
+    Rational,
+    String,
+    Char,
+    Symbol,
+    // NOTE:  A symbol naming a native.  It is a Symbol that can also be used as the native's function (see
+    // Infer::unify_inner), since calling a symbol calls the native with that name.
+    Native(String),
+    List(Box<Type>),
+    Tuple(Vec<Type>),
+    Map(Box<Type>, Box<Type>),
+    Fun(Vec<Type>, Box<Type>),
+}
+
+// NOTE:  A type that is polymorphic over vars (from let generalization).
+#[derive(Debug, Clone)]
+pub struct Scheme {
+    pub vars : Vec<usize>,
+    pub ty : Type,
+}
+
+impl Scheme {
+    pub fn mono(ty : Type) -> Self {
+        Scheme { vars: vec![], ty }
+    }
+
+    pub fn free_vars(&self) -> Vec<usize> {
+        self.ty.free_vars().into_iter().filter(|v| !self.vars.contains(v)).collect()
+    }
+}
+
+impl Type {
+    pub fn free_vars(&self) -> Vec<usize> {
+        fn vars(t : &Type, out : &mut Vec<usize>) {
+            match t {
+                Type::Var(x) if !out.contains(x) => out.push(*x),
+                Type::List(t) => vars(t, out),
+                Type::Tuple(ts) => ts.iter().for_each(|t| vars(t, out)),
+                Type::Map(k, v) => { vars(k, out); vars(v, out); },
+                Type::Fun(ps, r) => { ps.iter().for_each(|t| vars(t, out)); vars(r, out); },
+                _ => { },
+            }
+        }
+        let mut out = vec![];
+        vars(self, &mut out);
+        out
+    }
+}
+
+// NOTE:  Type variables are named a, b, c, ... in the order they appear.  Types that are shown together share names
+// so that the same variable reads the same in an error message.
+pub fn display_together(types : &[&Type]) -> Vec<String> {
+    fn name(index : usize) -> String {
+        let letter = (b'a' + (index % 26) as u8) as char;
+        match index / 26 {
+            0 => letter.to_string(),
+            n => format!("{}{}", letter, n),
+        }
+    }
+
+    fn show(t : &Type, names : &mut HashMap<usize, String>) -> String {
+        fn all(ts : &[Type], names : &mut HashMap<usize, String>) -> String {
+            ts.iter().map(|t| show(t, names)).collect::<Vec<_>>().join(", ")
+        }
+        match t {
+            Type::Var(x) => {
+                let next = names.len();
+                names.entry(*x).or_insert_with(|| name(next)).clone()
+            },
+            Type::Rigid(_, name) => name.clone(),
+            Type::Number => "Number".into(),
+            Type::Int => "Int".into(),
+            Type::BigInt => "BigInt".into(),
+            Type::Rational => "Rational".into(),
+            Type::String => "String".into(),
+            Type::Char => "Char".into(),
+            Type::Symbol | Type::Native(_) => "Symbol".into(),
+            Type::List(t) => format!("List<{}>", show(t, names)),
+            Type::Tuple(ts) => format!("{{{}}}", all(ts, names)),
+            Type::Map(k, v) => format!("Map<{}, {}>", show(k, names), show(v, names)),
+            Type::Fun(ps, r) => format!("fun({}) : {}", all(ps, names), show(r, names)),
+        }
+    }
+
+    let mut names = HashMap::new();
+    types.iter().map(|t| show(t, &mut names)).collect()
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", display_together(&[self])[0])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_name_vars_in_order_of_appearance() {
+        let t = Type::Fun(vec![Type::Var(7), Type::List(Box::new(Type::Var(3)))], Box::new(Type::Var(7)));
+
+        assert_eq!( t.to_string(), "fun(a, List<b>) : a" );
+    }
+
+    #[test]
+    fn should_share_names_when_displayed_together() {
+        let output = display_together(&[&Type::Var(2), &Type::Tuple(vec![Type::Var(1), Type::Var(2)])]);
+
+        assert_eq!( output, vec!["a".to_string(), "{b, a}".to_string()] );
+    }
+}